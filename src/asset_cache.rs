@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+use sdl2::image::LoadTexture;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::video::WindowContext;
+
+pub struct AssetCache<'a, C = WindowContext>{
+	//everything the renderers draw from disk, loaded the first time it's asked for and kept for the rest of the game
+	texture_creator: &'a TextureCreator<C>,
+	ttf_context: &'a Sdl2TtfContext,
+	textures: HashMap<String, Texture<'a>>,
+	fonts: HashMap<u16, Font<'a, 'static>>,
+	text: HashMap<(String, u16, Color), Texture<'a>>,
+	missing: HashSet<String>, //optional textures we already failed to load, so we don't hit the disk every frame
+	render_targets: HashMap<String, (Option<u64>, Texture<'a>)>, //drawn by the game itself, and the revision they were last drawn at
+}
+
+impl<'a, C> AssetCache<'a, C>{
+	//C is whatever the textures get drawn onto, a window in the game and a plain surface in tests
+	pub fn new(texture_creator: &'a TextureCreator<C>, ttf_context: &'a Sdl2TtfContext) -> AssetCache<'a, C>{
+		AssetCache{
+			texture_creator,
+			ttf_context,
+			textures: HashMap::new(),
+			fonts: HashMap::new(),
+			text: HashMap::new(),
+			missing: HashSet::new(),
+			render_targets: HashMap::new(),
+		}
+	}
+
+	pub fn font_path() -> &'static str{
+		"assets/fonts/The_Frontman.ttf"
+	}
+
+	fn max_cached_text() -> usize{
+		//text that changes every frame (timers, counters) would otherwise grow the cache forever
+		256
+	}
+
+	pub fn texture(&mut self, path: &str) -> &Texture<'a>{
+		if !self.textures.contains_key(path) {
+			let texture = self.texture_creator.load_texture(path)
+					.unwrap_or_else(|error| panic!("Unable to load texture {}: {}", path, error));
+			self.textures.insert(String::from(path), texture);
+		}
+		&self.textures[path]
+	}
+
+	pub fn try_texture(&mut self, path: &str) -> Option<&Texture<'a>>{
+		if self.missing.contains(path) {
+			return None;
+		}
+		if !self.textures.contains_key(path) {
+			match self.texture_creator.load_texture(path) {
+				Ok(texture) => {
+					self.textures.insert(String::from(path), texture);
+				},
+				Err(error) => {
+					println!("Unable to load texture {}: {}", path, error);
+					self.missing.insert(String::from(path));
+					return None;
+				}
+			}
+		}
+		self.textures.get(path)
+	}
+
+	pub fn cached_render<T: RenderTarget<Context = C>>(&mut self, canvas: &mut Canvas<T>, key: &str, revision: u64, size: (u32, u32), draw: impl FnOnce(&mut Canvas<T>)) -> &Texture<'a>{
+		//only calls draw when the revision has moved on since last time, otherwise hands back what it drew then
+		let size_matches = self.render_targets.get(key).is_some_and(|(_, texture)| {
+			let query = texture.query();
+			(query.width, query.height) == size
+		});
+		if !size_matches {
+			let texture = self.texture_creator.create_texture_target(None, size.0, size.1).unwrap();
+			self.render_targets.insert(String::from(key), (None, texture));
+		}
+		let (drawn_revision, texture) = self.render_targets.get_mut(key).unwrap();
+		if *drawn_revision != Some(revision) {
+			canvas.with_texture_canvas(texture, draw).unwrap();
+			*drawn_revision = Some(revision);
+		}
+		&self.render_targets[key].1
+	}
+
+	pub fn text(&mut self, text: &str, font_size: u16, color: Color) -> &Texture<'a>{
+		let key = (String::from(text), font_size, color);
+		if !self.text.contains_key(&key) {
+			if self.text.len() >= Self::max_cached_text() {
+				self.text.clear();
+			}
+			let ttf_context = self.ttf_context;
+			let font = self.fonts
+					.entry(font_size)
+					.or_insert_with(|| ttf_context.load_font(Self::font_path(), font_size).unwrap());
+			let surface = font.render(text)
+					.blended(color)
+					.unwrap();
+			let texture = self.texture_creator.create_texture_from_surface(&surface).unwrap();
+			self.text.insert(key.clone(), texture);
+		}
+		&self.text[&key]
+	}
+}
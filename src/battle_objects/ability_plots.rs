@@ -1,9 +1,11 @@
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
 use crate::battle_objects::battle_player::Ability;
 use crate::battle_objects::coordinates::GridCoord;
 use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
 
 #[derive(Clone)]
 pub struct AbilityPlot {
@@ -11,8 +13,20 @@ pub struct AbilityPlot {
 	pub(crate) ability: Ability
 }
 
-impl BattleRenderable for AbilityPlot{
-	fn render(&self, canvas: &mut WindowCanvas, _background_texture: &Texture, ctx: &BattleContext){
+impl<T: RenderTarget> BattleRenderable<T> for AbilityPlot{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Floor
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.center().y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos.center(), GridCoord::grid_size() as u32))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
 		let camera_pos = ctx.camera_state.pos;
 		let camera_scale = ctx.camera_state.scale;
 		let plot_rect = Rect::from_center(
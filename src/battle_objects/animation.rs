@@ -0,0 +1,129 @@
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::Direction;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Playback{
+	Loop,
+	Once, //holds on the last frame until something else starts playing
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClip{
+	pub first_frame: u32, //column of the sheet the clip starts on, each facing direction has its own row
+	pub frame_durations: &'static [u32], //in ticks
+	pub playback: Playback,
+}
+
+impl AnimationClip{
+	pub fn frame_size() -> u32{
+		16
+	}
+
+	pub fn sheet_row(facing: Direction) -> u32{
+		match facing {
+			Direction::North => 0,
+			Direction::East => 1,
+			Direction::South => 2,
+			Direction::West => 3,
+		}
+	}
+
+	pub fn frame_at(&self, elapsed: u32) -> u32{
+		let total: u32 = self.frame_durations.iter().sum();
+		let mut remaining = match self.playback {
+			Playback::Loop => elapsed % total,
+			Playback::Once if elapsed >= total => return self.frame_durations.len() as u32 - 1,
+			Playback::Once => elapsed,
+		};
+		for (frame, duration) in self.frame_durations.iter().enumerate() {
+			if remaining < *duration {
+				return frame as u32;
+			}
+			remaining -= duration;
+		}
+		self.frame_durations.len() as u32 - 1
+	}
+
+	pub fn source_rect(&self, elapsed: u32, facing: Direction) -> Rect{
+		let size = AnimationClip::frame_size();
+		Rect::new(
+			((self.first_frame + self.frame_at(elapsed)) * size) as i32,
+			(AnimationClip::sheet_row(facing) * size) as i32,
+			size,
+			size
+		)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationKey{
+	PlayerIdle,
+	PlayerWalk,
+	PlayerLearn,
+	PlayerBuild,
+	PlayerAttack,
+	PlayerRepair,
+	PlayerHeal,
+	PlayerPress,
+	EnemyIdle,
+	EnemyWalk,
+	EnemyAttack,
+	EnemyShoot,
+}
+
+impl AnimationKey{
+	pub fn clip(&self) -> AnimationClip{
+		//columns match the layout of player_sheet.png and the enemy sheets
+		let (first_frame, frame_durations, playback): (u32, &'static [u32], Playback) = match self {
+			AnimationKey::PlayerIdle => (0, &[20, 20], Playback::Loop),
+			AnimationKey::PlayerWalk => (2, &[4, 4, 4, 4], Playback::Loop),
+			AnimationKey::PlayerLearn => (6, &[8, 8], Playback::Loop),
+			AnimationKey::PlayerBuild => (8, &[6, 6, 6], Playback::Loop),
+			AnimationKey::PlayerAttack => (11, &[3, 4, 8], Playback::Once),
+			AnimationKey::PlayerRepair => (14, &[6, 6], Playback::Loop),
+			AnimationKey::PlayerHeal => (16, &[10, 10], Playback::Loop),
+			AnimationKey::PlayerPress => (18, &[5, 5], Playback::Once),
+			AnimationKey::EnemyIdle => (0, &[25, 25], Playback::Loop),
+			AnimationKey::EnemyWalk => (2, &[5, 5, 5, 5], Playback::Loop),
+			AnimationKey::EnemyAttack => (6, &[6, 4, 8], Playback::Loop),
+			AnimationKey::EnemyShoot => (9, &[4, 12], Playback::Loop),
+		};
+		AnimationClip{ first_frame, frame_durations, playback }
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Animator{
+	pub key: AnimationKey,
+	pub elapsed: u32,
+}
+
+impl Animator{
+	pub fn new(key: AnimationKey) -> Animator{
+		Animator{ key, elapsed: 0 }
+	}
+
+	pub fn play(&mut self, key: AnimationKey){
+		//called once a tick, switching clips starts the new one from its first frame
+		if self.key == key {
+			self.elapsed += 1;
+		} else {
+			self.key = key;
+			self.elapsed = 0;
+		}
+	}
+
+	pub fn render<T: RenderTarget>(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, sheet: &str, facing: Direction, target: Rect) -> bool{
+		//false if the sheet couldn't be loaded, so the caller can draw something plainer instead
+		let source = self.key.clip().source_rect(self.elapsed, facing);
+		match assets.try_texture(sheet) {
+			Some(texture) => {
+				canvas.copy(texture, source, target).unwrap();
+				true
+			},
+			None => false,
+		}
+	}
+}
@@ -0,0 +1,67 @@
+use crate::battle_objects::ability_plots::AbilityPlot;
+use crate::battle_objects::battle_player::Ability::{Armor, Build, ButtonPress, Heal, MeleeAttack, RangeAttack, Repair, Vision};
+use crate::battle_objects::buildables::{Wall, Window};
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::battle_objects::enemy::EnemyKind;
+use crate::battle_objects::terrain::TerrainMap;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnPoint{
+	pub pos: GridCoord,
+	pub kind: EnemyKind,
+}
+
+#[derive(Clone)]
+pub struct ArenaLayout{
+	//everything needed to set up a battle before anyone has moved
+	pub button: GridCoord,
+	pub player_start: GameCoord,
+	pub ability_plots: Vec<AbilityPlot>,
+	pub walls: Vec<Wall>,
+	pub windows: Vec<Window>,
+	pub spawn_points: Vec<SpawnPoint>,
+	pub terrain: TerrainMap,
+}
+
+impl ArenaLayout{
+	pub fn classic() -> ArenaLayout{
+		//the original hand-built arena, centered on the origin
+		let spawn = |x: i32, y: i32, kind: EnemyKind| SpawnPoint{ pos: GridCoord{x, y}, kind };
+		ArenaLayout{
+			button: GridCoord{x:0, y:0},
+			player_start: GameCoord{ x:150, y:-150 },
+			ability_plots: vec![
+				AbilityPlot{pos:GridCoord{x:-1, y:-2}, ability:MeleeAttack},
+				AbilityPlot{pos:GridCoord{x:1,  y:-2}, ability:Armor},
+				AbilityPlot{pos:GridCoord{x:2,  y:-1}, ability:RangeAttack},
+				AbilityPlot{pos:GridCoord{x:2,  y:1}, ability:Vision},
+				AbilityPlot{pos:GridCoord{x:1,  y:2}, ability:Build},
+				AbilityPlot{pos:GridCoord{x:-1, y:2}, ability:Repair},
+				AbilityPlot{pos:GridCoord{x:-2, y:-1}, ability:ButtonPress},
+				AbilityPlot{pos:GridCoord{x:-2, y:1}, ability:Heal}
+			],
+			walls: Vec::new(),
+			windows: Vec::new(),
+			spawn_points: vec![
+				spawn(10, 10, EnemyKind::Melee),
+				spawn(10, -10, EnemyKind::Melee),
+				spawn(-10, 10, EnemyKind::Ranged),
+				spawn(-10, -10, EnemyKind::Melee),
+				spawn(12, 12, EnemyKind::Ranged),
+				spawn(9, 11, EnemyKind::Melee),
+			],
+			terrain: TerrainMap::new(),
+		}
+	}
+
+	pub fn boss_spawn_point(&self) -> GridCoord{
+		//bosses come in from whichever spawn point is farthest from the button
+		self.spawn_points
+				.iter()
+				.map(|spawn| spawn.pos)
+				.max_by(|first, second| {
+					first.pythagorean_distance_to(&self.button).partial_cmp(&second.pythagorean_distance_to(&self.button)).unwrap()
+				})
+				.unwrap_or(GridCoord{x: self.button.x, y: self.button.y - 20})
+	}
+}
@@ -1,10 +1,15 @@
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{Canvas, RenderTarget};
 use sdl2::rect::{Point, Rect};
 use sdl2::pixels::Color;
-use crate::battle_objects::buildables::Wall;
-use crate::battle_objects::coordinates::{Direction, GameCoord};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::animation::{AnimationKey, Animator};
+use crate::battle_objects::buildables::{BuildSite, Buildable, WallStore};
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::terrain::{Terrain, TerrainMap};
 use crate::screens::battle::{ActionButton, BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
 use crate::utils::collisions::line_to_square_intersect;
+use crate::utils::edge_store::Edge;
 use crate::utils::render_utils::render_progress_bar;
 
 #[derive(Clone, Copy)]
@@ -12,19 +17,70 @@ pub struct BattlePlayerContext{
 	pub game_coord: GameCoord,
 	pub facing_vector: f32,
 	pub base_vision_range: u8,
+	pub health: (usize, usize),
 	pub ability_primary: Ability,
 	pub ability_secondary: Ability,
 	pub snapped_facing_vector: Direction,
 	pub state: PlayerState,
+	pub build_selection: Buildable,
+	pub materials: u32, //spent on anything the build ability makes
+	pub animation: Animator,
 }
 
 impl BattlePlayerContext{
-	const fn width() -> u32{
+	pub(crate) const fn width() -> u32{
 		16
 	}
 
-	pub const fn running_speed() -> f32{
-		3.0
+	pub fn sprite_sheet() -> &'static str{
+		"assets/images/player_sheet.png"
+	}
+
+	pub fn running_speed(terrain: Terrain) -> f32{
+		3.0 * terrain.speed_multiplier()
+	}
+
+	pub fn run(&mut self, terrain: &TerrainMap){
+		//step along the facing vector, refusing to walk into water or pits
+		let running_speed = BattlePlayerContext::running_speed(terrain.get(self.game_coord.to_grid_coord()));
+		let next_coord = GameCoord{
+			x: self.game_coord.x + (self.facing_vector.cos() * running_speed) as i32,
+			y: self.game_coord.y - (self.facing_vector.sin() * running_speed) as i32,
+		};
+		if terrain.allows_step(self.game_coord, next_coord) {
+			self.game_coord = next_coord;
+		}
+	}
+
+	pub fn facing_edge(&self) -> (GameCoord, GameCoord){
+		//the edge of our square we're facing, which is where anything we build goes
+		self.game_coord.to_grid_coord().edge(self.snapped_facing_vector)
+	}
+
+	pub fn facing_square(&self) -> GridCoord{
+		let square = self.game_coord.to_grid_coord();
+		match self.snapped_facing_vector {
+			Direction::North => square.to_north(1),
+			Direction::South => square.to_south(1),
+			Direction::East => square.to_east(1),
+			Direction::West => square.to_west(1),
+		}
+	}
+
+	pub fn build_site(&self) -> BuildSite{
+		if self.build_selection.builds_on_square() {
+			BuildSite::Square(self.facing_square())
+		} else {
+			BuildSite::Edge(self.facing_edge())
+		}
+	}
+
+	pub fn build_time(&self, walls: &WallStore) -> u32{
+		self.build_selection.build_time(walls.get(self.facing_edge()))
+	}
+
+	pub fn has_ability(&self, ability: Ability) -> bool{
+		self.ability_primary == ability || self.ability_secondary == ability
 	}
 
 	fn display_corners(&self, width: u32, scale_factor: f32, center_point: GameCoord, window_dimensions:(u32, u32)) -> (Point, Point, Point, Point){
@@ -58,28 +114,28 @@ impl BattlePlayerContext{
 		}
 	}
 
-	pub(crate) fn get_collisions(&self, top_wall: Option<&Wall>, right_wall: Option<&Wall>, bottom_wall: Option<&Wall>, left_wall: Option<&Wall>) -> (bool, bool, bool, bool){
+	pub(crate) fn get_collisions(&self, top_wall: Option<Edge>, right_wall: Option<Edge>, bottom_wall: Option<Edge>, left_wall: Option<Edge>) -> (bool, bool, bool, bool){
 		let mut collisions = (false, false, false, false);
 		let player_square = ||-> ((i32, i32),u32){
 			(self.game_coord.into(), BattlePlayerContext::width())
 		};
 		if let Some(wall) = top_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.0 = true;
 			}
 		}
 		if let Some(wall) = right_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.1 = true;
 			}
 		}
 		if let Some(wall) = bottom_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.2 = true;
 			}
 		}
 		if let Some(wall) = left_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.3 = true;
 			}
 		}
@@ -87,8 +143,20 @@ impl BattlePlayerContext{
 	}
 }
 
-impl BattleRenderable for BattlePlayerContext{
-	fn render(&self, canvas: &mut WindowCanvas, _background_texture: &Texture, ctx: &BattleContext){
+impl<T: RenderTarget> BattleRenderable<T> for BattlePlayerContext{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Bodies
+	}
+
+	fn sort_key(&self) -> i32{
+		self.game_coord.y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.game_coord, 2 * BattlePlayerContext::width()))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
 		let player = &ctx.player;
 		let camera = &ctx.camera_state;
 		let canvas_size = canvas.output_size().unwrap();
@@ -100,21 +168,15 @@ impl BattleRenderable for BattlePlayerContext{
 			(camera.scale * BattlePlayerContext::width() as f32) as u32,
 			(camera.scale * BattlePlayerContext::width() as f32) as u32);
 		let player_facing_indicator_points = player.edge_coords(BattlePlayerContext::width(), camera.scale, camera.pos, canvas_size);
-		let player_color = match player.state{
-			PlayerState::Standing => Color::RED,
-			PlayerState::Running => Color::YELLOW,
-			PlayerState::Learning(_,_,_) => Color::RGB(255, 127, 0),
-			PlayerState::BuildPlacing(_,_) => Color::RGB(255, 127,0),
-			PlayerState::MeleeAttacking(_, _) => Color::RGB(255, 127, 0),
-			PlayerState::RangeTargeting => Color::RGB(255, 127, 0),
-			PlayerState::RangeAttacking(_,_) => Color::RGB(255, 127, 0),
-			PlayerState::ButtonPressing(_,_) => Color::RGB(255, 127, 0),
-			PlayerState::BuildChoosing => Color::RGB(255, 127, 0),
-			PlayerState::Repairing(_,_) => Color::RGB(255, 127, 0),
-			PlayerState::Healing(_,_) => Color::RGB(255, 127, 0),
-		};
-		canvas.set_draw_color(player_color);
-		canvas.fill_rect(player_rect).unwrap();
+		if !player.animation.render(canvas, assets, BattlePlayerContext::sprite_sheet(), player.snapped_facing_vector, player_rect) {
+			let player_color = match player.state{
+				PlayerState::Standing => Color::RED,
+				PlayerState::Running => Color::YELLOW,
+				_ => Color::RGB(255, 127, 0),
+			};
+			canvas.set_draw_color(player_color);
+			canvas.fill_rect(player_rect).unwrap();
+		}
 		let mut render_progress = |cur, max| {
 			render_progress_bar(
 				canvas,
@@ -170,7 +232,26 @@ pub enum PlayerState{
 	Healing(u32, u32)
 }
 
-#[derive(Clone, Copy)]
+impl PlayerState{
+	pub fn animation(&self) -> AnimationKey{
+		match self {
+			PlayerState::Standing => AnimationKey::PlayerIdle,
+			PlayerState::Running => AnimationKey::PlayerWalk,
+			PlayerState::Learning(_, _, _) => AnimationKey::PlayerLearn,
+			PlayerState::MeleeAttacking(_, _) => AnimationKey::PlayerAttack,
+			//aiming and firing are one swing of the same clip
+			PlayerState::RangeTargeting => AnimationKey::PlayerAttack,
+			PlayerState::RangeAttacking(_, _) => AnimationKey::PlayerAttack,
+			PlayerState::ButtonPressing(_, _) => AnimationKey::PlayerPress,
+			PlayerState::BuildChoosing => AnimationKey::PlayerBuild,
+			PlayerState::BuildPlacing(_, _) => AnimationKey::PlayerBuild,
+			PlayerState::Repairing(_, _) => AnimationKey::PlayerRepair,
+			PlayerState::Healing(_, _) => AnimationKey::PlayerHeal,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Ability{
 	Blank,
 	MeleeAttack,
@@ -184,6 +265,15 @@ pub enum Ability{
 }
 
 impl Ability{
+	pub fn all() -> [Ability; 8]{
+		//every learnable ability, in the order they're offered in menus
+		[Ability::MeleeAttack, Ability::Armor, Ability::RangeAttack, Ability::Vision, Ability::Build, Ability::Repair, Ability::ButtonPress, Ability::Heal]
+	}
+
+	pub fn from_name(name: &str) -> Option<Ability>{
+		Ability::all().into_iter().find(|ability| format!("{:?}", ability) == name)
+	}
+
 	pub fn get_hud_text(&self) -> String{
 		match self{
 			Ability::Blank => String::from(""),
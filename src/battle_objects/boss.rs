@@ -0,0 +1,288 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::battle_player::BattlePlayerContext;
+use crate::battle_objects::buildables::{DoorStore, WallStore};
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::terrain::TerrainMap;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
+use crate::utils::collisions::{line_to_square_intersect, square_to_square_overlap};
+use crate::utils::pathing::path_to_with_footprint;
+use crate::utils::visibility::visible_squares_from;
+
+#[derive(Clone)]
+pub struct Boss{
+	//pos is the center of the boss's footprint
+	pub pos: GameCoord,
+	pub snapped_facing_vector: Direction,
+	pub health: (u32, u32),
+	pub behavior: BossBehavior,
+	pub ability_cooldown: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossPhase{
+	Advancing,
+	Enraged,
+	Desperate,
+}
+
+#[derive(Clone)]
+pub enum BossBehavior{
+	Idle,
+	WalkToButton(u32, u32, Vec<GridCoord>),
+	Charging(u32, u32, f32), //windup and dash in a straight line at the given angle
+	Summoning(u32, u32),
+	Smashing(u32, u32),
+	Stuck(u32, u32), //couldn't get any closer, wait a while before pathing again
+}
+
+#[derive(Clone, Debug)]
+pub enum BossAction{
+	SpawnMinions(Vec<GameCoord>),
+	SmashWalls(GameCoord, i32), //center and radius in game units
+	Trample(GameCoord, usize), //a charge ran into someone, hurts every player under the boss at that spot
+}
+
+impl Boss {
+	pub fn new(pos: GameCoord) -> Boss {
+		Boss{
+			pos,
+			snapped_facing_vector: Direction::South,
+			health: (1000, 1000),
+			behavior: BossBehavior::Idle,
+			ability_cooldown: Boss::ability_cooldown_time(),
+		}
+	}
+
+	pub fn footprint() -> i32 {
+		//side length in grid squares, odd so the boss always has a center square
+		3
+	}
+
+	pub(crate) fn width() -> u32 {
+		(Boss::footprint() * GridCoord::grid_size() - 4) as u32
+	}
+
+	pub fn speed() -> f32 {
+		1.0
+	}
+
+	pub fn charge_speed() -> f32 {
+		4.5
+	}
+
+	pub fn vision_range() -> u8 {
+		9
+	}
+
+	pub fn ability_cooldown_time() -> u32 {
+		300
+	}
+
+	pub fn charge_damage() -> usize {
+		30
+	}
+
+	pub fn smash_radius() -> i32 {
+		2 * GridCoord::grid_size()
+	}
+
+	pub fn phase(&self) -> BossPhase {
+		match self.health.0 as f32 / self.health.1 as f32 {
+			x if x > 0.66 => BossPhase::Advancing,
+			x if x > 0.33 => BossPhase::Enraged,
+			_ => BossPhase::Desperate,
+		}
+	}
+
+	pub fn footprint_squares(&self) -> Vec<GridCoord> {
+		let center = self.pos.to_grid_coord();
+		let reach = Boss::footprint() / 2;
+		(-reach..=reach)
+			.flat_map(|x| (-reach..=reach).map(move |y| center.offset((x, y))))
+			.collect()
+	}
+
+	fn collides_with_walls(pos: GameCoord, walls: &WallStore, doors: &DoorStore) -> bool {
+		let reach = Boss::footprint() / 2 + 1;
+		let crosses_body = |endpoints: &(GameCoord, GameCoord)| {
+			line_to_square_intersect((endpoints.0.into(), endpoints.1.into()), (pos.into(), Boss::width()))
+		};
+		walls.near(pos.to_grid_coord(), reach).iter().any(|wall| crosses_body(&wall.endpoints)) ||
+				doors.near(pos.to_grid_coord(), reach).iter().any(|door| crosses_body(&door.endpoints))
+	}
+
+	fn walls_in_smash_range(&self, walls: &WallStore, doors: &DoorStore) -> bool {
+		let reach = Boss::smash_radius() / GridCoord::grid_size() + 1;
+		let in_range = |endpoints: &(GameCoord, GameCoord)| {
+			endpoints.0.pythagorean_distance_to(&self.pos) <= Boss::smash_radius() as f32 ||
+					endpoints.1.pythagorean_distance_to(&self.pos) <= Boss::smash_radius() as f32
+		};
+		walls.near(self.pos.to_grid_coord(), reach).iter().any(|wall| in_range(&wall.endpoints)) ||
+				doors.near(self.pos.to_grid_coord(), reach).iter().any(|door| in_range(&door.endpoints))
+	}
+
+	fn sees(&self, target: GameCoord, walls: &WallStore) -> bool {
+		let own_square = self.pos.to_grid_coord();
+		let target_square = target.to_grid_coord();
+		if own_square.pythagorean_distance_to(&target_square) > Boss::vision_range() as f32 {
+			return false;
+		}
+		visible_squares_from(own_square, self.snapped_facing_vector, Boss::vision_range(), walls).contains(&target_square)
+	}
+
+	fn try_step(&mut self, angle: f32, speed: f32, walls: &WallStore, doors: &DoorStore, terrain: &TerrainMap) -> bool {
+		//returns false if a wall is in the way, the boss is too big to be snapped back like smaller enemies
+		let speed = speed * terrain.get(self.pos.to_grid_coord()).speed_multiplier();
+		let next_pos = GameCoord{
+			x: self.pos.x + (angle.cos() * speed) as i32,
+			y: self.pos.y + (angle.sin() * speed) as i32,
+		};
+		if Boss::collides_with_walls(next_pos, walls, doors) || !terrain.allows_step(self.pos, next_pos) {
+			return false;
+		}
+		if let Some(direction) = Direction::from_movement(self.pos, next_pos) {
+			self.snapped_facing_vector = direction;
+		}
+		self.pos = next_pos;
+		true
+	}
+
+	fn choose_behavior(&mut self, player_pos: GameCoord, button: GridCoord, walls: &WallStore, doors: &DoorStore, terrain: &TerrainMap) -> BossBehavior {
+		let phase = self.phase();
+		let sees_player = self.sees(player_pos, walls);
+		let angle_to_player = (player_pos.y as f32 - self.pos.y as f32).atan2(player_pos.x as f32 - self.pos.x as f32);
+		match phase {
+			BossPhase::Enraged if self.ability_cooldown == 0 => {
+				self.ability_cooldown = Boss::ability_cooldown_time();
+				return BossBehavior::Summoning(0, 45);
+			},
+			BossPhase::Desperate if self.walls_in_smash_range(walls, doors) && self.ability_cooldown == 0 => {
+				self.ability_cooldown = Boss::ability_cooldown_time() / 2;
+				return BossBehavior::Smashing(0, 30);
+			},
+			BossPhase::Enraged | BossPhase::Desperate if sees_player => {
+				return BossBehavior::Charging(0, 45, angle_to_player);
+			},
+			_ => ()
+		}
+		match path_to_with_footprint(self.pos.to_grid_coord(), button, walls, doors, &Vec::new(), terrain, Boss::footprint()) {
+			Some(path) => BossBehavior::WalkToButton(0, 150, path),
+			//walled off, so break through whatever is closest
+			None if self.walls_in_smash_range(walls, doors) => BossBehavior::Smashing(0, 30),
+			None => BossBehavior::WalkToButton(0, 150, vec![button]),
+		}
+	}
+
+	pub fn update(&mut self, player_pos: GameCoord, button: GridCoord, walls: &WallStore, doors: &DoorStore, terrain: &TerrainMap) -> Vec<BossAction> {
+		let mut actions = Vec::new();
+		self.ability_cooldown = self.ability_cooldown.saturating_sub(1);
+		self.behavior = match self.behavior.clone() {
+			BossBehavior::Idle => self.choose_behavior(player_pos, button, walls, doors, terrain),
+			BossBehavior::WalkToButton(curr, max, _) if curr >= max => BossBehavior::Idle,
+			BossBehavior::WalkToButton(curr, max, path) => {
+				match path.first() {
+					Some(next_square) if self.pos.to_grid_coord() == *next_square => {
+						BossBehavior::WalkToButton(curr + 1, max, path[1..].to_vec())
+					},
+					Some(next_square) => {
+						let target = next_square.center();
+						let angle = (target.y as f32 - self.pos.y as f32).atan2(target.x as f32 - self.pos.x as f32);
+						if self.try_step(angle, Boss::speed(), walls, doors, terrain) {
+							BossBehavior::WalkToButton(curr + 1, max, path)
+						} else {
+							//blocked with nothing to smash, replanning straight away would only hit the same wall every tick
+							BossBehavior::Stuck(0, 30)
+						}
+					},
+					None => BossBehavior::Idle,
+				}
+			},
+			BossBehavior::Charging(curr, max, _) if curr >= max => BossBehavior::Idle,
+			BossBehavior::Charging(curr, max, angle) if curr < 15 => {
+				//wind up before charging so the player has a chance to dodge
+				BossBehavior::Charging(curr + 1, max, angle)
+			},
+			BossBehavior::Charging(curr, max, angle) => {
+				if self.try_step(angle, Boss::charge_speed(), walls, doors, terrain) {
+					let player_body = (player_pos.into(), BattlePlayerContext::width());
+					if square_to_square_overlap((self.pos.into(), Boss::width()), player_body).is_some() {
+						//the charge ends on whoever it hits
+						actions.push(BossAction::Trample(self.pos, Boss::charge_damage()));
+						BossBehavior::Idle
+					} else {
+						BossBehavior::Charging(curr + 1, max, angle)
+					}
+				} else {
+					if self.phase() == BossPhase::Desperate {
+						actions.push(BossAction::SmashWalls(self.pos, Boss::smash_radius()));
+					}
+					BossBehavior::Idle
+				}
+			},
+			BossBehavior::Summoning(curr, max) if curr >= max => {
+				let reach = (Boss::footprint() / 2 + 1) * GridCoord::grid_size();
+				actions.push(BossAction::SpawnMinions(vec![
+					GameCoord{x: self.pos.x - reach, y: self.pos.y},
+					GameCoord{x: self.pos.x + reach, y: self.pos.y},
+				]));
+				BossBehavior::Idle
+			},
+			BossBehavior::Summoning(curr, max) => BossBehavior::Summoning(curr + 1, max),
+			BossBehavior::Smashing(curr, max) if curr >= max => {
+				actions.push(BossAction::SmashWalls(self.pos, Boss::smash_radius()));
+				BossBehavior::Idle
+			},
+			BossBehavior::Smashing(curr, max) => BossBehavior::Smashing(curr + 1, max),
+			BossBehavior::Stuck(curr, max) if curr >= max => BossBehavior::Idle,
+			BossBehavior::Stuck(curr, max) => BossBehavior::Stuck(curr + 1, max),
+		};
+		actions
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for Boss{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Bodies
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos, Boss::width() + GridCoord::grid_size() as u32))
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		self.footprint_squares().iter().any(|square| ctx.fog.is_visible(*square))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera_pos = ctx.camera_state.pos;
+		let camera_scale = ctx.camera_state.scale;
+		let boss_rect = Rect::from_center(
+			self.pos.to_display_coord(
+				camera_pos,
+				camera_scale,
+				canvas.output_size().unwrap()
+			),
+			(camera_scale*Boss::width() as f32) as u32,
+			(camera_scale*Boss::width() as f32) as u32
+		);
+		let boss_color = match (&self.behavior, self.phase()) {
+			(BossBehavior::Charging(_, _, _), _) | (BossBehavior::Smashing(_, _), _) => Color::RGB(255, 64, 0),
+			(BossBehavior::Summoning(_, _), _) => Color::RGB(192, 0, 192),
+			(_, BossPhase::Advancing) => Color::RGB(96, 0, 64),
+			(_, BossPhase::Enraged) => Color::RGB(160, 0, 48),
+			(_, BossPhase::Desperate) => Color::RGB(224, 0, 0),
+		};
+		canvas.set_draw_color(boss_color);
+		canvas.fill_rect(boss_rect).unwrap();
+		canvas.set_draw_color(Color::RGB(128, 0, 128));
+		canvas.draw_rect(boss_rect).unwrap();
+	}
+}
@@ -1,36 +1,235 @@
 use sdl2::pixels::Color;
-use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::faction::Faction;
+use crate::battle_objects::traps::TrapKind;
 use crate::screens::battle::BattleRenderable;
+use crate::screens::render_queue::{RenderLayer, Bounds, bounding_box};
+use crate::utils::edge_store::{Edge, EdgeStore, OnEdge};
 
 #[derive(Clone, Copy)]
 pub struct Wall{
 	//walls are corner-aligned. To convert them to game coordinates, default to the top left
 	pub endpoints: (GameCoord, GameCoord),
 	pub health: (usize, usize),
+	pub material: WallMaterial,
+	pub spent: u32, //materials players have put into this wall, walls that came with the arena start at 0
 }
 
-impl Wall {
-	pub fn is_blocking(&self, first: GridCoord, second: GridCoord) -> bool{
-		//if both of my coordinates are coordinates in both grid corners, return true, else false
-		let first_grid_coords = vec![first.top_left(), first.top_right(), first.bottom_left(), first.bottom_right()];
-		let second_grid_coords = vec![second.top_left(), second.top_right(), second.bottom_left(), second.bottom_right()];
-		let mut bordering_first_grid_count: u8 = 0;
-		first_grid_coords
-				.iter()
-				.for_each(|coord| {
-					if self.endpoints.0 == *coord || self.endpoints.1 == *coord{
-						bordering_first_grid_count+=1;
-					}
-				});
-		let mut bordering_second_grid_count: u8=0;
-		second_grid_coords
-				.iter()
-				.for_each(|coord| {
-					if self.endpoints.0 == *coord || self.endpoints.1 == *coord{
-						bordering_second_grid_count+=1;
-					}
-				});
-		return bordering_first_grid_count == 2  && bordering_second_grid_count == 2;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WallMaterial{
+	Wood, //what every new wall starts as
+	Stone,
+	Reinforced,
+}
+
+impl WallMaterial{
+	pub fn all() -> [WallMaterial; 3]{
+		[WallMaterial::Wood, WallMaterial::Stone, WallMaterial::Reinforced]
+	}
+
+	pub fn from_name(name: &str) -> Option<WallMaterial>{
+		WallMaterial::all().into_iter().find(|material| format!("{:?}", material) == name)
+	}
+
+	pub fn next_tier(existing: Option<&Wall>) -> Option<WallMaterial>{
+		//new walls go up as wood, building over an existing wall upgrades it a tier
+		match existing {
+			None => Some(WallMaterial::Wood),
+			Some(wall) => wall.material.upgrade(),
+		}
+	}
+
+	pub fn upgrade(&self) -> Option<WallMaterial>{
+		match self {
+			WallMaterial::Wood => Some(WallMaterial::Stone),
+			WallMaterial::Stone => Some(WallMaterial::Reinforced),
+			WallMaterial::Reinforced => None,
+		}
+	}
+
+	pub fn max_health(&self) -> usize{
+		match self {
+			WallMaterial::Wood => 60,
+			WallMaterial::Stone => 100,
+			WallMaterial::Reinforced => 200,
+		}
+	}
+
+	pub fn build_time(&self) -> u32{
+		match self {
+			WallMaterial::Wood => 15,
+			WallMaterial::Stone => 30,
+			WallMaterial::Reinforced => 45,
+		}
+	}
+
+	pub fn cost(&self) -> u32{
+		match self {
+			WallMaterial::Wood => 1,
+			WallMaterial::Stone => 2,
+			WallMaterial::Reinforced => 4,
+		}
+	}
+
+	pub fn damage_multiplier(&self) -> f32{
+		//how much of each hit actually gets through
+		match self {
+			WallMaterial::Wood => 1.0,
+			WallMaterial::Stone => 0.75,
+			WallMaterial::Reinforced => 0.5,
+		}
+	}
+}
+
+impl Wall{
+	pub fn new(endpoints: (GameCoord, GameCoord), material: WallMaterial) -> Wall{
+		Wall{ endpoints, health: (material.max_health(), material.max_health()), material, spent: 0 }
+	}
+
+	pub fn salvage_value(&self) -> u32{
+		//half of whatever players spent on it, so tearing down the arena's own walls gives nothing back
+		self.spent / 2
+	}
+
+	pub fn upgrade_to(&mut self, material: WallMaterial){
+		//keep any damage the wall has already taken, the new material only adds to it
+		let added_health = material.max_health().saturating_sub(self.health.1);
+		self.health = (self.health.0 + added_health, material.max_health());
+		self.material = material;
+		self.spent += material.cost();
+	}
+
+	pub fn resisted_damage(&self, damage: usize) -> usize{
+		(damage as f32 * self.material.damage_multiplier()).ceil() as usize
+	}
+}
+
+impl OnEdge for Wall{
+	fn edge(&self) -> Edge{
+		self.endpoints
+	}
+}
+
+pub type WallStore = EdgeStore<Wall>;
+
+#[derive(Clone, Copy)]
+pub enum WallChange{
+	//queued whenever a wall or door is added to or removed from the battle so cached enemy paths can be repaired
+	Built(Edge),
+	Destroyed(Edge),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Buildable{
+	Wall,
+	Door,
+	Trap(TrapKind),
+	Turret,
+	Deconstruct, //not really a buildable, takes down whatever wall or door is on the edge instead
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum BuildSite{
+	//walls and doors go on the edge the player is facing, everything else goes in the square beyond it
+	Edge(Edge),
+	Square(GridCoord),
+}
+
+impl Buildable{
+	pub fn next(&self) -> Buildable{
+		match self {
+			Buildable::Wall => Buildable::Door,
+			Buildable::Door => Buildable::Trap(TrapKind::Spikes),
+			Buildable::Trap(TrapKind::Spikes) => Buildable::Trap(TrapKind::Tar),
+			Buildable::Trap(TrapKind::Tar) => Buildable::Trap(TrapKind::Alarm),
+			Buildable::Trap(TrapKind::Alarm) => Buildable::Turret,
+			Buildable::Turret => Buildable::Deconstruct,
+			Buildable::Deconstruct => Buildable::Wall,
+		}
+	}
+
+	pub fn build_time(&self, existing_wall: Option<&Wall>) -> u32{
+		match self {
+			Buildable::Wall => WallMaterial::next_tier(existing_wall).map_or(25, |material| material.build_time()),
+			//taking a wall apart is as slow as putting up its current tier
+			Buildable::Deconstruct => existing_wall.map_or(25, |wall| wall.material.build_time()),
+			_ => 25,
+		}
+	}
+
+	pub fn cost(&self, existing_wall: Option<&Wall>) -> u32{
+		match self {
+			Buildable::Wall => WallMaterial::next_tier(existing_wall).map_or(0, |material| material.cost()),
+			Buildable::Door => 2,
+			Buildable::Trap(_) => 1,
+			Buildable::Turret => 4,
+			Buildable::Deconstruct => 0,
+		}
+	}
+
+	pub fn builds_on_square(&self) -> bool{
+		matches!(self, Buildable::Trap(_) | Buildable::Turret)
+	}
+
+	pub fn get_hud_text(&self) -> String{
+		match self {
+			Buildable::Wall => String::from("Wall"),
+			Buildable::Door => String::from("Door"),
+			Buildable::Trap(TrapKind::Spikes) => String::from("Spike Trap"),
+			Buildable::Trap(TrapKind::Tar) => String::from("Tar Trap"),
+			Buildable::Trap(TrapKind::Alarm) => String::from("Tripwire"),
+			Buildable::Turret => String::from("Turret"),
+			Buildable::Deconstruct => String::from("Deconstruct"),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct Door{
+	//doors are corner-aligned like walls. Friendly bodies swing them open, everything hostile has to break them down
+	pub endpoints: (GameCoord, GameCoord),
+	pub health: (usize, usize),
+	pub open_ticks: u32, //how much longer the door stays open, 0 when closed
+}
+
+impl Door {
+	pub fn new(endpoints: (GameCoord, GameCoord)) -> Door{
+		Door{ endpoints, health: (60, 60), open_ticks: 0 }
+	}
+
+	pub fn hold_open_time() -> u32{
+		20
+	}
+
+	pub fn is_open(&self) -> bool{
+		self.open_ticks > 0
+	}
+
+	pub fn blocks(&self, faction: Faction) -> bool{
+		match faction {
+			//friendly bodies are held back until update_doors swings the door open for them
+			Faction::Friendly => !self.is_open(),
+			Faction::Hostile => true,
+		}
+	}
+}
+
+impl OnEdge for Door{
+	fn edge(&self) -> Edge{
+		self.endpoints
+	}
+}
+
+pub type DoorStore = EdgeStore<Door>;
+
+impl DoorStore{
+	pub fn is_blocking_for(&self, first: GridCoord, second: GridCoord, faction: Faction) -> bool{
+		self.between(first, second).map_or(false, |door| door.blocks(faction))
+	}
+
+	pub fn on_side_for(&self, square: GridCoord, side: Direction, faction: Faction) -> Option<&Door>{
+		self.on_side(square, side).filter(|door| door.blocks(faction))
 	}
 }
 
@@ -41,8 +240,16 @@ pub struct Window{
 	pub health: (usize, usize),
 }
 
-impl BattleRenderable for Wall{
-	fn render(&self, canvas: &mut sdl2::render::WindowCanvas, _background_texture: &sdl2::render::Texture, ctx: &crate::screens::battle::BattleContext){
+impl<T: sdl2::render::RenderTarget> BattleRenderable<T> for Wall{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Structures
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(bounding_box(self.endpoints.0, self.endpoints.1))
+	}
+
+	fn render(&self, canvas: &mut sdl2::render::Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &crate::screens::battle::BattleContext){
 		//set color to green if health is full, red if health is 0, yellow if health is in between
 		let camera_coord = ctx.camera_state.pos;
 		let camera_scale = ctx.camera_state.scale;
@@ -53,15 +260,66 @@ impl BattleRenderable for Wall{
 			_ => unreachable!("Health ratio outside of 0 to 1 range")
 		};
 		canvas.set_draw_color(draw_color);
-		canvas.draw_line(
-			self.endpoints.0.to_display_coord(camera_coord, camera_scale ,canvas.output_size().unwrap()),
-			self.endpoints.1.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap())
-		).unwrap();
+		let start = self.endpoints.0.to_display_coord(camera_coord, camera_scale ,canvas.output_size().unwrap());
+		let end = self.endpoints.1.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap());
+		canvas.draw_line(start, end).unwrap();
+		//sturdier materials are drawn thicker
+		let offset = if start.x() == end.x() { (1, 0) } else { (0, 1) };
+		if let WallMaterial::Stone | WallMaterial::Reinforced = self.material {
+			canvas.draw_line(start.offset(offset.0, offset.1), end.offset(offset.0, offset.1)).unwrap();
+		}
+		if let WallMaterial::Reinforced = self.material {
+			canvas.draw_line(start.offset(-offset.0, -offset.1), end.offset(-offset.0, -offset.1)).unwrap();
+		}
+	}
+}
+
+impl<T: sdl2::render::RenderTarget> BattleRenderable<T> for Door{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Structures
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(bounding_box(self.endpoints.0, self.endpoints.1))
+	}
+
+	fn render(&self, canvas: &mut sdl2::render::Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &crate::screens::battle::BattleContext){
+		//closed doors are a thick brown line, open ones are swung out square from the first endpoint
+		let camera_coord = ctx.camera_state.pos;
+		let camera_scale = ctx.camera_state.scale;
+		let start = self.endpoints.0.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap());
+		let end = self.endpoints.1.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap());
+		canvas.set_draw_color(Color::RGB(160, 96, 32));
+		if self.is_open() {
+			let swung_end = start.offset(end.y() - start.y(), end.x() - start.x());
+			canvas.draw_line(start, swung_end).unwrap();
+		} else {
+			let offset = if start.x() == end.x() { (1, 0) } else { (0, 1) };
+			canvas.draw_line(start, end).unwrap();
+			canvas.draw_line(start.offset(offset.0, offset.1), end.offset(offset.0, offset.1)).unwrap();
+			canvas.draw_line(start.offset(-offset.0, -offset.1), end.offset(-offset.0, -offset.1)).unwrap();
+		}
 	}
 }
 
-impl BattleRenderable for Window{
-	fn render(&self, _canvas: &mut sdl2::render::WindowCanvas, _background_texture: &sdl2::render::Texture, _ctx: &crate::screens::battle::BattleContext){
-		todo!("Implement window rendering")
+impl<T: sdl2::render::RenderTarget> BattleRenderable<T> for Window{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Structures
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(bounding_box(self.endpoints.0, self.endpoints.1))
+	}
+
+	fn render(&self, canvas: &mut sdl2::render::Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &crate::screens::battle::BattleContext){
+		//windows are drawn as a pale double line so they read differently from walls
+		let camera_coord = ctx.camera_state.pos;
+		let camera_scale = ctx.camera_state.scale;
+		let start = self.endpoints.0.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap());
+		let end = self.endpoints.1.to_display_coord(camera_coord, camera_scale, canvas.output_size().unwrap());
+		let offset = if start.x() == end.x() { (1, 0) } else { (0, 1) };
+		canvas.set_draw_color(Color::RGB(128, 200, 255));
+		canvas.draw_line(start, end).unwrap();
+		canvas.draw_line(start.offset(offset.0, offset.1), end.offset(offset.0, offset.1)).unwrap();
 	}
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
-use sdl2::image::LoadTexture;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
 use crate::battle_objects::coordinates::GridCoord;
 use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
 
 #[derive(Clone)]
 pub enum ButtonState{
@@ -30,13 +31,24 @@ impl Button {
 	}
 }
 
-impl BattleRenderable for Button{
-	fn render(&self, canvas: &mut WindowCanvas, _background_texture: &Texture, ctx: &BattleContext) {
-		let texture_creator = canvas.texture_creator();
+impl<T: RenderTarget> BattleRenderable<T> for Button{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Floor
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.center().y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos.center(), GridCoord::grid_size() as u32))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext) {
 		let button_texture = match self.state {
-			ButtonState::NeverPressed => texture_creator.load_texture("assets/images/hotel_bell_gray.png").unwrap(),
-			ButtonState::Unpressed(_, _) => texture_creator.load_texture("assets/images/hotel_bell_gray.png").unwrap(),
-			ButtonState::Pressed(_, _) => texture_creator.load_texture("assets/images/hotel_bell_yellow.png").unwrap(),
+			ButtonState::NeverPressed => assets.texture("assets/images/hotel_bell_gray.png"),
+			ButtonState::Unpressed(_, _) => assets.texture("assets/images/hotel_bell_gray.png"),
+			ButtonState::Pressed(_, _) => assets.texture("assets/images/hotel_bell_yellow.png"),
 		};
 		let camera = &ctx.camera_state;
 		let display_rect_center = self.pos.center().to_display_coord(camera.pos, camera.scale, canvas.output_size().unwrap());
@@ -44,6 +56,6 @@ impl BattleRenderable for Button{
 			display_rect_center,
 			(camera.scale * 16.0) as u32,
 			(camera.scale * 16.0) as u32);
-		canvas.copy(&button_texture, None, Some(button_rect)).unwrap();
+		canvas.copy(button_texture, None, Some(button_rect)).unwrap();
 	}
 }
\ No newline at end of file
@@ -1,20 +1,126 @@
+use std::f32::consts::PI;
 use crate::battle_objects::coordinates::GameCoord;
+use crate::utils::random::SeededRng;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode{
+	FollowPlayer,
+	FrameAllPlayers, //co-op, zooms out as far as it has to so nobody walks off screen
+}
 
 #[derive(Clone)]
 pub struct CameraState{
-	pub pos: GameCoord,
+	pub pos: GameCoord, //what the screen is centered on this frame, focus plus any shake
 	pub scale: f32,
+	pub focus: GameCoord,
+	pub zoom: f32, //the scale the player asked for, framing can only ever zoom out from here
+	pub trauma: f32, //0 to 1, decays every tick, shake grows with the square of it
+	pub mode: CameraMode,
+	pub viewport: (u32, u32), //size of the screen in pixels, for working out how much fits on it
+	mode_button_held: bool,
+	rng: SeededRng,
 }
 
 impl CameraState{
 	pub fn new() -> CameraState{
-		CameraState{pos: GameCoord{x:0, y:0}, scale:1.1}
+		CameraState{
+			pos: GameCoord{x:0, y:0},
+			scale: 1.1,
+			focus: GameCoord{x:0, y:0},
+			zoom: 1.1,
+			trauma: 0.0,
+			mode: CameraMode::FollowPlayer,
+			viewport: (1080, 720),
+			mode_button_held: false,
+			rng: SeededRng::new(0xCA3E7A),
+		}
+	}
+
+	pub fn min_zoom() -> f32{
+		0.5
+	}
+
+	pub fn max_zoom() -> f32{
+		2.5
+	}
+
+	fn zoom_speed() -> f32{
+		//scale change per tick with the stick all the way over
+		0.03
+	}
+
+	fn max_shake() -> f32{
+		//pixels of offset at full trauma
+		10.0
+	}
+
+	fn trauma_decay() -> f32{
+		0.04
+	}
+
+	fn framing_margin() -> i32{
+		//room left around the outermost players, in game units
+		60
+	}
+
+	pub fn adjust_zoom(&mut self, amount: f32){
+		//amount runs from -1 (out) to 1 (in)
+		self.zoom = (self.zoom + amount * CameraState::zoom_speed()).clamp(CameraState::min_zoom(), CameraState::max_zoom());
+	}
+
+	pub fn add_trauma(&mut self, amount: f32){
+		self.trauma = (self.trauma + amount).min(1.0);
+	}
+
+	pub fn update_mode_button(&mut self, pressed: bool){
+		//switch modes once per press rather than every tick it's held
+		if pressed && !self.mode_button_held {
+			self.mode = match self.mode {
+				CameraMode::FollowPlayer => CameraMode::FrameAllPlayers,
+				CameraMode::FrameAllPlayers => CameraMode::FollowPlayer,
+			};
+		}
+		self.mode_button_held = pressed;
 	}
 
 	pub fn smooth_scroll(&mut self, target: &GameCoord){
-		let dx = target.x - self.pos.x;
-		let dy = target.y - self.pos.y;
-		self.pos.x = self.pos.x + (0.1 * dx as f32) as i32;
-		self.pos.y = self.pos.y + (0.1 * dy as f32) as i32;
+		self.ease_towards(target, self.zoom);
 	}
-}
\ No newline at end of file
+
+	pub fn frame_players(&mut self, players: &Vec<GameCoord>){
+		//center on everyone and zoom out until they all fit, but never in past the chosen zoom
+		let Some(first) = players.first() else { return };
+		let (mut min_x, mut max_x, mut min_y, mut max_y) = (first.x, first.x, first.y, first.y);
+		for player in players {
+			min_x = min_x.min(player.x);
+			max_x = max_x.max(player.x);
+			min_y = min_y.min(player.y);
+			max_y = max_y.max(player.y);
+		}
+		let center = GameCoord{x: (min_x + max_x)/2, y: (min_y + max_y)/2};
+		let span_x = (max_x - min_x + 2 * CameraState::framing_margin()) as f32;
+		let span_y = (max_y - min_y + 2 * CameraState::framing_margin()) as f32;
+		let fitted_scale = (self.viewport.0 as f32 / span_x).min(self.viewport.1 as f32 / span_y);
+		self.ease_towards(&center, fitted_scale.clamp(CameraState::min_zoom(), self.zoom));
+	}
+
+	fn ease_towards(&mut self, target: &GameCoord, target_scale: f32){
+		let dx = target.x - self.focus.x;
+		let dy = target.y - self.focus.y;
+		self.focus.x += (0.1 * dx as f32) as i32;
+		self.focus.y += (0.1 * dy as f32) as i32;
+		self.scale += 0.1 * (target_scale - self.scale);
+		self.shake();
+	}
+
+	fn shake(&mut self){
+		let intensity = self.trauma * self.trauma * CameraState::max_shake();
+		let angle = self.rng.next_f32() * 2.0 * PI;
+		let distance = intensity * self.rng.next_f32();
+		self.pos = GameCoord{
+			x: self.focus.x + (angle.cos() * distance) as i32,
+			y: self.focus.y + (angle.sin() * distance) as i32,
+		};
+		self.trauma = (self.trauma - CameraState::trauma_decay()).max(0.0);
+	}
+}
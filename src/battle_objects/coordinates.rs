@@ -111,6 +111,15 @@ impl GridCoord {
 	pub fn pythagorean_distance_to(&self, other: &GridCoord) -> f32 {
 		(((self.x - other.x).pow(2) + (self.y - other.y).pow(2)) as f32).sqrt()
 	}
+	pub fn edge(&self, side: Direction) -> (GameCoord, GameCoord) {
+		//the corner-aligned endpoints of one side of this square, the same way walls store them
+		match side {
+			Direction::North => (self.top_left(), self.top_right()),
+			Direction::South => (self.bottom_left(), self.bottom_right()),
+			Direction::West => (self.top_left(), self.bottom_left()),
+			Direction::East => (self.top_right(), self.bottom_right()),
+		}
+	}
 }
 
 impl PartialEq for GridCoord {
@@ -138,4 +147,21 @@ impl Direction{
 			_ => {unreachable!("impossible value in facing vector")}
 		}
 	}
+
+	pub(crate) fn to_facing_vector(&self) -> f32{
+		match self {
+			Direction::East => 0.0,
+			Direction::North => 0.5*PI,
+			Direction::West => PI,
+			Direction::South => -0.5*PI,
+		}
+	}
+
+	pub(crate) fn from_movement(from: GameCoord, to: GameCoord) -> Option<Direction>{
+		//game coordinates grow downwards, facing vectors grow upwards
+		if from == to {
+			return None;
+		}
+		Some(Direction::from_facing_vector(((from.y - to.y) as f32).atan2((to.x - from.x) as f32)))
+	}
 }
\ No newline at end of file
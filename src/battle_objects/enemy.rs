@@ -1,64 +1,310 @@
+use std::collections::HashMap;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
-use crate::battle_objects::buildables::Wall;
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::animation::{AnimationKey, Animator};
+use crate::battle_objects::buildables::{DoorStore, WallChange, WallStore};
 use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::faction::Faction;
+use crate::battle_objects::projectiles::Projectile;
+use crate::battle_objects::terrain::{Terrain, TerrainMap};
+use crate::battle_objects::turret::Turret;
 use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
 use crate::utils::collisions::line_to_square_intersect;
+use crate::utils::edge_store::{normalize_edge, Edge};
+use crate::utils::pathing::{path_crosses_wall, path_passes_near_wall, path_to};
+use crate::utils::visibility::{has_line_of_sight, visible_squares_from};
 
 #[derive(Clone)]
 pub struct Enemy{
 	pub pos: GameCoord,
 	pub snapped_facing_vector: Direction,
 	pub health: (u32, u32),
-	pub behavior: EnemyBehavior
+	pub behavior: EnemyBehavior,
+	pub kind: EnemyKind,
+	pub attack_cooldown: u32,
+	pub slowed_ticks: u32, //how much longer tar or anything else is holding us back
+	pub animation: Animator,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnemyKind{
+	Melee,
+	Ranged, //keeps its distance and shoots at players and walls
 }
 
 impl Enemy {
-	pub fn speed() -> f32 {
-		1.5
+	pub fn new(pos: GameCoord, kind: EnemyKind) -> Enemy {
+		Enemy{
+			pos,
+			snapped_facing_vector: Direction::North,
+			health: (100, 100),
+			behavior: EnemyBehavior::Idle,
+			kind,
+			attack_cooldown: 0,
+			slowed_ticks: 0,
+			animation: Animator::new(AnimationKey::EnemyIdle),
+		}
 	}
-	const fn width() -> u32 {
+
+	pub fn speed(terrain: Terrain) -> f32 {
+		1.5 * terrain.speed_multiplier()
+	}
+	pub(crate) const fn width() -> u32 {
 		16
 	}
 
+	pub fn sprite_sheet(&self) -> &'static str{
+		match self.kind {
+			EnemyKind::Melee => "assets/images/enemy_melee_sheet.png",
+			EnemyKind::Ranged => "assets/images/enemy_ranged_sheet.png",
+		}
+	}
+
+	pub fn vision_range() -> u8 {
+		7
+	}
+
+	pub fn hearing_range() -> f32 {
+		3.0
+	}
+
+	pub fn alert_range() -> f32 {
+		6.0
+	}
+
+	pub fn ranged_attack_range() -> f32 {
+		6.0
+	}
+
+	pub fn barrier_search_reach() -> i32 {
+		//how many squares out a boxed-in enemy looks for something to break through
+		8
+	}
+
+	pub fn ranged_retreat_range() -> f32 {
+		3.0
+	}
+
+	pub fn ranged_attack_cooldown() -> u32 {
+		45
+	}
+
+	pub fn in_ranged_attack_position(&self, target: GameCoord, walls: &WallStore, ignored: Option<Edge>) -> bool{
+		if self.kind != EnemyKind::Ranged {
+			return false;
+		}
+		let distance = self.pos.to_grid_coord().pythagorean_distance_to(&target.to_grid_coord());
+		distance <= Enemy::ranged_attack_range() && has_line_of_sight(self.pos, target, walls, ignored)
+	}
+
+	pub fn try_fire_at(&mut self, target: GameCoord) -> Option<Projectile>{
+		if self.attack_cooldown > 0 {
+			return None;
+		}
+		self.attack_cooldown = Enemy::ranged_attack_cooldown();
+		if let Some(direction) = Direction::from_movement(self.pos, target) {
+			self.snapped_facing_vector = direction;
+		}
+		Some(Projectile::new(Faction::Hostile, self.pos, target, Enemy::attack_power() as i32))
+	}
+
+	fn separation_radius() -> f32 {
+		GridCoord::grid_size() as f32
+	}
+
 	pub fn attack_power() -> u32 {
 		12
 	}
 
-	pub fn get_collisions(&self, top_wall: Option<&Wall>, right_wall: Option<&Wall>,
-	                      bottom_wall: Option<&Wall>, left_wall: Option<&Wall>
+	pub fn melee_attack_cooldown() -> u32 {
+		30
+	}
+
+	pub fn adjacent_barrier(&self, walls: &WallStore, doors: &DoorStore) -> Option<Edge>{
+		//the wall or door on one of the sides of our square, checking the way we're facing first
+		let square = self.pos.to_grid_coord();
+		let sides = match self.snapped_facing_vector {
+			Direction::North => [Direction::North, Direction::East, Direction::West, Direction::South],
+			Direction::South => [Direction::South, Direction::East, Direction::West, Direction::North],
+			Direction::East => [Direction::East, Direction::North, Direction::South, Direction::West],
+			Direction::West => [Direction::West, Direction::North, Direction::South, Direction::East],
+		};
+		sides.iter().find_map(|&side| {
+			walls.on_side(square, side).map(|wall| wall.endpoints)
+					.or_else(|| doors.on_side_for(square, side, Faction::Hostile).map(|door| door.endpoints))
+		})
+	}
+
+	pub fn adjacent_turret(&self, turrets: &HashMap<GridCoord, Turret>, walls: &WallStore, doors: &DoorStore) -> Option<GridCoord>{
+		//a turret in a neighboring square that we can reach without going through a wall or door
+		let square = self.pos.to_grid_coord();
+		[square.to_north(1), square.to_south(1), square.to_east(1), square.to_west(1)]
+				.into_iter()
+				.find(|neighbor| {
+					turrets.contains_key(neighbor) &&
+							!walls.is_blocking(square, *neighbor) &&
+							!doors.is_blocking_for(square, *neighbor, Faction::Hostile)
+				})
+	}
+
+	pub fn try_strike(&mut self) -> bool{
+		//melee hits share the attack cooldown with shooting
+		if self.attack_cooldown > 0 {
+			return false;
+		}
+		self.attack_cooldown = Enemy::melee_attack_cooldown();
+		true
+	}
+
+	pub fn get_collisions(&self, top_wall: Option<Edge>, right_wall: Option<Edge>,
+	                      bottom_wall: Option<Edge>, left_wall: Option<Edge>
 	) -> (bool, bool, bool, bool){
 		let mut collisions = (false, false, false, false);
 		let player_square = ||-> ((i32, i32),u32){
 			(self.pos.into(), Enemy::width())
 		};
 		if let Some(wall) = top_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.0 = true;
 			}
 		}
 		if let Some(wall) = right_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.1 = true;
 			}
 		}
 		if let Some(wall) = bottom_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.2 = true;
 			}
 		}
 		if let Some(wall) = left_wall {
-			if line_to_square_intersect((wall.endpoints.0.into(), wall.endpoints.1.into()), player_square()){
+			if line_to_square_intersect((wall.0.into(), wall.1.into()), player_square()){
 				collisions.3 = true;
 			}
 		}
 		collisions
 	}
+
+	pub fn perceives(&self, target: GridCoord, target_is_noisy: bool, walls: &WallStore) -> bool{
+		//noisy targets can be heard through walls, everything else has to be seen
+		let own_square = self.pos.to_grid_coord();
+		let distance = own_square.pythagorean_distance_to(&target);
+		if target_is_noisy && distance <= Enemy::hearing_range() {
+			return true;
+		}
+		if distance > Enemy::vision_range() as f32 {
+			return false;
+		}
+		visible_squares_from(own_square, self.snapped_facing_vector, Enemy::vision_range(), walls).contains(&target)
+	}
+
+	fn separation_vector(&self, neighbors: &Vec<GameCoord>) -> (f32, f32){
+		//push away from every neighbor inside the separation radius, harder the closer they are
+		let radius = Enemy::separation_radius();
+		let mut separation = (0.0, 0.0);
+		for neighbor in neighbors {
+			let distance = self.pos.pythagorean_distance_to(neighbor);
+			if distance >= radius {
+				continue;
+			}
+			if distance == 0.0 {
+				//stacked exactly on top of each other, there's no direction to push in yet
+				continue;
+			}
+			let weight = (radius - distance) / radius;
+			separation.0 += (self.pos.x - neighbor.x) as f32 / distance * weight;
+			separation.1 += (self.pos.y - neighbor.y) as f32 / distance * weight;
+		}
+		separation
+	}
+
+	pub fn steered_step_towards(&self, target: GameCoord, neighbors: &Vec<GameCoord>, terrain: &TerrainMap) -> GameCoord{
+		let seek_angle = (target.y as f32 - self.pos.y as f32).atan2(target.x as f32 - self.pos.x as f32);
+		let separation = self.separation_vector(neighbors);
+		let steering = (seek_angle.cos() + 1.5 * separation.0, seek_angle.sin() + 1.5 * separation.1);
+		let angle = steering.1.atan2(steering.0);
+		let slow_multiplier = if self.slowed_ticks > 0 { 0.4 } else { 1.0 };
+		let speed = Enemy::speed(terrain.get(self.pos.to_grid_coord())) * slow_multiplier;
+		let next_pos = GameCoord{
+			x: self.pos.x + (angle.cos() * speed) as i32,
+			y: self.pos.y + (angle.sin() * speed) as i32,
+		};
+		//never let steering carry us into water or a pit, the path already routes around them
+		if terrain.allows_step(self.pos, next_pos) { next_pos } else { self.pos }
+	}
+
+	pub fn handle_wall_changes(&mut self, changes: &Vec<WallChange>, walls: &WallStore, doors: &DoorStore, blocked_squares: &Vec<GridCoord>, terrain: &TerrainMap){
+		if let EnemyBehavior::ShootWalls(_, _, target) = self.behavior {
+			//only stop shooting once the wall we were aiming at is the one that came down
+			let target_destroyed = changes.iter().any(|change| match change {
+				WallChange::Destroyed(wall) => GameCoord{x: (wall.0.x + wall.1.x)/2, y: (wall.0.y + wall.1.y)/2} == target,
+				WallChange::Built(_) => false,
+			});
+			if target_destroyed {
+				self.behavior = EnemyBehavior::Idle;
+			}
+			return;
+		}
+		let Some(path) = self.behavior.path() else { return };
+		let current_square = self.pos.to_grid_coord();
+		//attacking enemies end their path next to the wall they're going to hit
+		let destination = path.last().copied().unwrap_or(current_square);
+		let borders_destination = |wall: Edge| -> bool {
+			[Direction::North, Direction::South, Direction::West, Direction::East]
+					.iter()
+					.any(|side| normalize_edge(destination.edge(*side)) == normalize_edge(wall))
+		};
+		let mut needs_replan = false;
+		for change in changes {
+			match (change, &self.behavior) {
+				(WallChange::Built(wall), _) if path_crosses_wall(current_square, path, *wall) => {
+					needs_replan = true;
+				},
+				(WallChange::Destroyed(wall), EnemyBehavior::AttackWalls(_, _, _)) if borders_destination(*wall) => {
+					//the wall we were heading for may be gone, go back to idle and pick a new target
+					self.behavior = EnemyBehavior::Idle;
+					return;
+				},
+				(WallChange::Destroyed(wall), _) if path_passes_near_wall(current_square, path, *wall) => {
+					needs_replan = true;
+				},
+				_ => ()
+			}
+		}
+		if !needs_replan {
+			return;
+		}
+		//repair the path locally: keep the same goal, but replan from the square we're standing in
+		let Some(&goal) = path.last() else { return };
+		self.behavior = match path_to(current_square, goal, walls, doors, blocked_squares, terrain) {
+			Some(new_path) => self.behavior.with_path(new_path),
+			None => EnemyBehavior::Idle,
+		};
+	}
 }
 
-impl BattleRenderable for Enemy{
-	fn render(&self, canvas: &mut WindowCanvas, _background_texture: &Texture, ctx: &BattleContext){
+impl<T: RenderTarget> BattleRenderable<T> for Enemy{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Bodies
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos, Enemy::width()))
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		ctx.fog.is_visible(self.pos.to_grid_coord())
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
 		let camera_pos = ctx.camera_state.pos;
 		let camera_scale = ctx.camera_state.scale;
 		let enemy_rect = Rect::from_center(
@@ -70,8 +316,13 @@ impl BattleRenderable for Enemy{
 			(camera_scale*Enemy::width() as f32) as u32,
 			(camera_scale*Enemy::width() as f32) as u32
 		);
-		canvas.set_draw_color(Color::RGB(128, 0, 128));
-		canvas.fill_rect(enemy_rect).unwrap();
+		if !self.animation.render(canvas, assets, self.sprite_sheet(), self.snapped_facing_vector, enemy_rect) {
+			canvas.set_draw_color(match self.kind {
+				EnemyKind::Melee => Color::RGB(128, 0, 128),
+				EnemyKind::Ranged => Color::RGB(192, 64, 96),
+			});
+			canvas.fill_rect(enemy_rect).unwrap();
+		}
 	}
 }
 
@@ -81,4 +332,72 @@ pub enum EnemyBehavior{
 	WalkToButton(u32, u32, Vec<GridCoord>), //path to the button
 	TargetPlayer(u32, u32, Vec<GridCoord>), //path to the nearest player
 	AttackWalls(u32, u32, Vec<GridCoord>),
-}
\ No newline at end of file
+	Skirmishing(u32, u32), //ranged enemies holding their distance from the player
+	ShootWalls(u32, u32, GameCoord), //ranged enemies shooting at the midpoint of a wall
+}
+
+impl EnemyBehavior{
+	pub fn path(&self) -> Option<&Vec<GridCoord>>{
+		match self {
+			EnemyBehavior::Idle => None,
+			EnemyBehavior::WalkToButton(_, _, path) => Some(path),
+			EnemyBehavior::TargetPlayer(_, _, path) => Some(path),
+			EnemyBehavior::AttackWalls(_, _, path) => Some(path),
+			EnemyBehavior::Skirmishing(_, _) => None,
+			EnemyBehavior::ShootWalls(_, _, _) => None,
+		}
+	}
+
+	pub fn animation(&self) -> AnimationKey{
+		match self {
+			EnemyBehavior::Idle => AnimationKey::EnemyIdle,
+			EnemyBehavior::WalkToButton(_, _, _) => AnimationKey::EnemyWalk,
+			EnemyBehavior::TargetPlayer(_, _, _) => AnimationKey::EnemyWalk,
+			//still walking up to the wall until the path runs out
+			EnemyBehavior::AttackWalls(_, _, path) if !path.is_empty() => AnimationKey::EnemyWalk,
+			EnemyBehavior::AttackWalls(_, _, _) => AnimationKey::EnemyAttack,
+			EnemyBehavior::Skirmishing(_, _) => AnimationKey::EnemyShoot,
+			EnemyBehavior::ShootWalls(_, _, _) => AnimationKey::EnemyShoot,
+		}
+	}
+
+	pub fn with_path(&self, new_path: Vec<GridCoord>) -> EnemyBehavior{
+		//keeps the timers running so a replan doesn't extend the behavior's time limit
+		match self {
+			EnemyBehavior::Idle => EnemyBehavior::Idle,
+			EnemyBehavior::WalkToButton(curr, max, _) => EnemyBehavior::WalkToButton(*curr, *max, new_path),
+			EnemyBehavior::TargetPlayer(curr, max, _) => EnemyBehavior::TargetPlayer(*curr, *max, new_path),
+			EnemyBehavior::AttackWalls(curr, max, _) => EnemyBehavior::AttackWalls(*curr, *max, new_path),
+			EnemyBehavior::Skirmishing(_, _) => self.clone(),
+			EnemyBehavior::ShootWalls(_, _, _) => self.clone(),
+		}
+	}
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn enemy_in_water_climbs_out(){
+		let mut terrain = TerrainMap::new();
+		terrain.set(GridCoord{x: 0, y: 0}, Terrain::Water);
+		let mut enemy = Enemy::new(GridCoord{x: 0, y: 0}.center(), EnemyKind::Melee);
+		let target = GridCoord{x: 3, y: 0}.center();
+		for _ in 0..60 {
+			enemy.pos = enemy.steered_step_towards(target, &Vec::new(), &terrain);
+		}
+		assert_eq!(terrain.get(enemy.pos.to_grid_coord()), Terrain::Floor);
+	}
+
+	#[test]
+	fn enemy_on_floor_never_steps_into_water(){
+		let mut terrain = TerrainMap::new();
+		terrain.set(GridCoord{x: 1, y: 0}, Terrain::Water);
+		let mut enemy = Enemy::new(GridCoord{x: 0, y: 0}.center(), EnemyKind::Melee);
+		let target = GridCoord{x: 3, y: 0}.center();
+		for _ in 0..60 {
+			enemy.pos = enemy.steered_step_towards(target, &Vec::new(), &terrain);
+		}
+		assert_eq!(enemy.pos.to_grid_coord(), GridCoord{x: 0, y: 0});
+	}
+}
@@ -0,0 +1,5 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Faction{
+	Friendly, //players and anything they build
+	Hostile,  //enemies and bosses
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::GridCoord;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::RenderLayer;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FogState{
+	Unseen,
+	Remembered, //seen before, but nobody is looking at it right now
+	Visible,
+}
+
+#[derive(Clone)]
+pub struct FogOfWar{
+	explored: HashSet<GridCoord>,
+	visible: HashSet<GridCoord>,
+	explored_bounds: Option<(GridCoord, GridCoord)>, //top left and bottom right of everything explored so far
+}
+
+impl FogOfWar{
+	pub fn new() -> FogOfWar{
+		FogOfWar{ explored: HashSet::new(), visible: HashSet::new(), explored_bounds: None }
+	}
+
+	pub fn update(&mut self, visible: HashSet<GridCoord>){
+		//visible should already include what every player on the team can see
+		for square in &visible {
+			self.explored_bounds = Some(match self.explored_bounds {
+				None => (*square, *square),
+				Some((min, max)) => (
+					GridCoord{x: min.x.min(square.x), y: min.y.min(square.y)},
+					GridCoord{x: max.x.max(square.x), y: max.y.max(square.y)}
+				),
+			});
+		}
+		self.explored.extend(visible.iter().copied());
+		self.visible = visible;
+	}
+
+	pub fn state(&self, square: GridCoord) -> FogState{
+		if self.visible.contains(&square) {
+			FogState::Visible
+		} else if self.explored.contains(&square) {
+			FogState::Remembered
+		} else {
+			FogState::Unseen
+		}
+	}
+
+	pub fn is_visible(&self, square: GridCoord) -> bool{
+		self.visible.contains(&square)
+	}
+
+	pub fn is_explored(&self, square: GridCoord) -> bool{
+		self.explored.contains(&square)
+	}
+
+	pub fn explored(&self) -> impl Iterator<Item = &GridCoord>{
+		self.explored.iter()
+	}
+
+	pub fn explored_count(&self) -> usize{
+		//only ever grows, so it doubles as a revision number
+		self.explored.len()
+	}
+
+	pub fn explored_bounds(&self) -> Option<(GridCoord, GridCoord)>{
+		self.explored_bounds
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for FogOfWar{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Fog
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		//black out anything never seen and dim anything only remembered, over every square on screen
+		let camera = &ctx.camera_state;
+		let canvas_size = canvas.output_size().unwrap();
+		let square_width = camera.scale * GridCoord::grid_size() as f32;
+		let reach_x = (canvas_size.0 as f32 / square_width / 2.0).ceil() as i32 + 1;
+		let reach_y = (canvas_size.1 as f32 / square_width / 2.0).ceil() as i32 + 1;
+		let center = camera.pos.to_grid_coord();
+		canvas.set_blend_mode(BlendMode::Blend);
+		for x in -reach_x..=reach_x {
+			for y in -reach_y..=reach_y {
+				let square = center.offset((x, y));
+				let color = match self.state(square) {
+					FogState::Visible => continue,
+					FogState::Remembered => Color::RGBA(0, 0, 16, 160),
+					FogState::Unseen => Color::RGB(0, 0, 16),
+				};
+				canvas.set_draw_color(color);
+				canvas.fill_rect(Rect::from_center(
+					square.center().to_display_coord(camera.pos, camera.scale, canvas_size),
+					square_width.ceil() as u32,
+					square_width.ceil() as u32
+				)).unwrap();
+			}
+		}
+		canvas.set_blend_mode(BlendMode::None);
+	}
+}
@@ -1,42 +1,85 @@
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use crate::battle_objects::battle_player::BattlePlayerContext;
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::battle_player::{Ability, BattlePlayerContext};
 use crate::screens::battle::BattleRenderable;
+use crate::screens::render_queue::RenderLayer;
+use crate::utils::layout::{Anchor, Layout};
 use crate::utils::render_utils::render_text;
 
 pub struct Hud{
 	pub health: (usize, usize),
 	pub ability_primary: String,
 	pub ability_secondary: String,
+	pub boss_health: Option<(u32, u32)>,
+	pub alarm: bool,
+	pub materials: Option<u32>,
+	pub build_feedback: Option<String>,
 }
 
 impl Hud{
 	pub fn from_player(&player: &BattlePlayerContext) -> Hud{
+		let ability_text = |ability: Ability| match ability {
+			Ability::Build => format!("Build: {}", player.build_selection.get_hud_text()),
+			_ => ability.get_hud_text(),
+		};
 		Hud{
-			health: (100,100),
-			ability_primary: player.ability_primary.get_hud_text(),
-			ability_secondary: player.ability_secondary.get_hud_text(),
+			health: player.health,
+			ability_primary: ability_text(player.ability_primary),
+			ability_secondary: ability_text(player.ability_secondary),
+			boss_health: None,
+			alarm: false,
+			materials: if player.has_ability(Ability::Build) { Some(player.materials) } else { None },
+			build_feedback: None,
 		}
 	}
 }
 
-impl BattleRenderable for Hud {
-	fn render(&self, canvas: &mut sdl2::render::WindowCanvas, _background_texture: &sdl2::render::Texture, _ctx: &crate::screens::battle::BattleContext) {
+impl<T: sdl2::render::RenderTarget> BattleRenderable<T> for Hud{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Interface
+	}
+
+	fn render(&self, canvas: &mut sdl2::render::Canvas<T>, assets: &mut AssetCache<T::Context>, _ctx: &crate::screens::battle::BattleContext) {
+		let layout = Layout::new(canvas.output_size().unwrap());
 		//render a health bar
 		canvas.set_draw_color(Color::RGB(64, 64, 64));
-		let health_bar_width = 300;
-		canvas.fill_rect(Rect::new(10, 10, health_bar_width, 30)).unwrap();
-		let fill_width = ((self.health.0 as f32 / self.health.1 as f32) * health_bar_width as f32) as u32;
+		let health_bar = layout.rect(Anchor::TopLeft, (10, 10), (300, 30));
+		canvas.fill_rect(health_bar).unwrap();
+		let fill_width = ((self.health.0 as f32 / self.health.1 as f32) * health_bar.width() as f32) as u32;
 		canvas.set_draw_color(Color::RED);
-		canvas.fill_rect(Rect::new(10, 10, fill_width, 30)).unwrap();
+		canvas.fill_rect(Rect::new(health_bar.x(), health_bar.y(), fill_width, health_bar.height())).unwrap();
 
-		let ttf_context = sdl2::ttf::init().unwrap();
 		if self.ability_primary.len()>0{
-			render_text(canvas, &ttf_context, &self.ability_primary, 32, Color::WHITE, Rect::new(10, 50, 200, 50));
+			render_text(canvas, assets, &self.ability_primary, 32, Color::WHITE, layout.rect(Anchor::TopLeft, (10, 50), (200, 50)));
 		}
 
 		if self.ability_secondary.len()>0{
-			render_text(canvas, &ttf_context, &self.ability_secondary, 32, Color::WHITE, Rect::new(10, 110, 200, 50));
+			render_text(canvas, assets, &self.ability_secondary, 32, Color::WHITE, layout.rect(Anchor::TopLeft, (10, 110), (200, 50)));
+		}
+
+		if let Some(materials) = self.materials{
+			render_text(canvas, assets, &format!("Materials: {}", materials), 32, Color::WHITE, layout.rect(Anchor::TopLeft, (10, 170), (200, 50)));
+		}
+
+		if let Some(reason) = &self.build_feedback{
+			render_text(canvas, assets, reason, 32, Color::RED, layout.rect(Anchor::TopLeft, (10, 230), (400, 50)));
+		}
+
+		if self.alarm{
+			render_text(canvas, assets, "Tripwire!", 32, Color::YELLOW, layout.rect(Anchor::TopRight, (10, 10), (200, 50)));
+		}
+
+		//render the boss health bar along the bottom of the screen
+		if let Some(boss_health) = self.boss_health {
+			let boss_bar = layout.rect(Anchor::Bottom, (0, 26), (648, 24));
+			let label = layout.rect(Anchor::Bottom, (-284, 54), (80, 36));
+			render_text(canvas, assets, "Boss", 32, Color::WHITE, label);
+			canvas.set_draw_color(Color::RGB(64, 64, 64));
+			canvas.fill_rect(boss_bar).unwrap();
+			let boss_fill_width = ((boss_health.0 as f32 / boss_health.1 as f32) * boss_bar.width() as f32) as u32;
+			canvas.set_draw_color(Color::RGB(160, 0, 128));
+			canvas.fill_rect(Rect::new(boss_bar.x(), boss_bar.y(), boss_fill_width, boss_bar.height())).unwrap();
 		}
 	}
 }
\ No newline at end of file
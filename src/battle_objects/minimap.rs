@@ -0,0 +1,147 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::RenderLayer;
+use crate::utils::layout::{Anchor, Layout};
+
+#[derive(Clone)]
+pub struct Minimap{
+	pub shown: bool,
+	toggle_held: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct MinimapTransform{
+	//maps game units onto the minimap texture
+	origin: GameCoord, //top left corner of the explored area
+	pixels_per_square: f32,
+}
+
+impl MinimapTransform{
+	fn project(&self, pos: GameCoord) -> Point{
+		let scale = self.pixels_per_square / GridCoord::grid_size() as f32;
+		Point::new(
+			((pos.x - self.origin.x) as f32 * scale) as i32,
+			((pos.y - self.origin.y) as f32 * scale) as i32
+		)
+	}
+
+	fn square_rect(&self, square: GridCoord) -> Rect{
+		let half = GridCoord::grid_size() / 2;
+		let top_left = self.project(GameCoord{x: square.center().x - half, y: square.center().y - half});
+		let size = self.pixels_per_square.ceil().max(1.0) as u32;
+		Rect::new(top_left.x(), top_left.y(), size, size)
+	}
+
+	fn marker(&self, pos: GameCoord, size: u32) -> Rect{
+		Rect::from_center(self.project(pos), size, size)
+	}
+}
+
+impl Minimap{
+	pub fn new() -> Minimap{
+		Minimap{ shown: true, toggle_held: false }
+	}
+
+	pub fn size() -> u32{
+		180
+	}
+
+	fn max_pixels_per_square() -> f32{
+		6.0
+	}
+
+	pub fn update_toggle(&mut self, pressed: bool){
+		//flip once per press rather than every tick it's held
+		if pressed && !self.toggle_held {
+			self.shown = !self.shown;
+		}
+		self.toggle_held = pressed;
+	}
+
+	fn transform(ctx: &BattleContext, size: u32) -> Option<MinimapTransform>{
+		//fit everything explored so far, biggest side first
+		let (min, max) = ctx.fog.explored_bounds()?;
+		let span = (max.x - min.x + 1).max(max.y - min.y + 1) as f32;
+		let half = GridCoord::grid_size() / 2;
+		Some(MinimapTransform{
+			origin: GameCoord{x: min.center().x - half, y: min.center().y - half},
+			pixels_per_square: (size as f32 / span).min(Minimap::max_pixels_per_square() * size as f32 / Minimap::size() as f32),
+		})
+	}
+
+	fn revision(ctx: &BattleContext) -> u64{
+		//every part of this only ever counts up, so the sum changes whenever any of them do
+		ctx.walls.revision() + ctx.doors.revision() + ctx.fog.explored_count() as u64
+	}
+
+	fn draw_static_layer<T: RenderTarget>(canvas: &mut Canvas<T>, ctx: &BattleContext, transform: MinimapTransform){
+		//explored ground and the walls and doors around it, only redrawn when one of those changes
+		canvas.set_draw_color(Color::RGB(0, 0, 16));
+		canvas.clear();
+		canvas.set_draw_color(Color::RGB(40, 40, 56));
+		for square in ctx.fog.explored() {
+			canvas.fill_rect(transform.square_rect(*square)).unwrap();
+		}
+		for square in ctx.fog.explored() {
+			for side in [Direction::North, Direction::South, Direction::East, Direction::West] {
+				let (color, endpoints) = if let Some(wall) = ctx.walls.on_side(*square, side) {
+					(Color::RGB(200, 200, 200), wall.endpoints)
+				} else if let Some(door) = ctx.doors.on_side(*square, side) {
+					(Color::RGB(160, 96, 32), door.endpoints)
+				} else {
+					continue;
+				};
+				canvas.set_draw_color(color);
+				canvas.draw_line(transform.project(endpoints.0), transform.project(endpoints.1)).unwrap();
+			}
+		}
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for Minimap{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Interface
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		if !self.shown {
+			return;
+		}
+		let panel = Layout::new(canvas.output_size().unwrap()).rect(Anchor::TopRight, (10, 70), (Minimap::size(), Minimap::size()));
+		let size = panel.width();
+		let Some(transform) = Minimap::transform(ctx, size) else { return };
+		let background = assets.cached_render(canvas, "minimap", Minimap::revision(ctx), (size, size), |target| {
+			Minimap::draw_static_layer(target, ctx, transform);
+		});
+		canvas.copy(background, None, panel).unwrap();
+		//everything that moves is drawn straight on top, there's only ever a handful of them
+		let on_panel = |pos: GameCoord, marker_size: u32| {
+			let marker = transform.marker(pos, marker_size);
+			Rect::new(marker.x() + panel.x(), marker.y() + panel.y(), marker.width(), marker.height())
+		};
+		for plot in ctx.ability_plots.iter().filter(|plot| ctx.fog.is_explored(plot.pos)) {
+			canvas.set_draw_color(Color::CYAN);
+			canvas.fill_rect(on_panel(plot.pos.center(), 4)).unwrap();
+		}
+		if ctx.fog.is_explored(ctx.button.pos) {
+			canvas.set_draw_color(Color::YELLOW);
+			canvas.fill_rect(on_panel(ctx.button.pos.center(), 6)).unwrap();
+		}
+		for enemy in ctx.enemies.iter().filter(|enemy| ctx.fog.is_visible(enemy.pos.to_grid_coord())) {
+			canvas.set_draw_color(Color::RGB(192, 64, 192));
+			canvas.fill_rect(on_panel(enemy.pos, 3)).unwrap();
+		}
+		for other_player in &ctx.other_players {
+			canvas.set_draw_color(Color::GREEN);
+			canvas.fill_rect(on_panel(other_player.grid_coord.center(), 4)).unwrap();
+		}
+		canvas.set_draw_color(Color::RED);
+		canvas.fill_rect(on_panel(ctx.player.game_coord, 4)).unwrap();
+		canvas.set_draw_color(Color::RGB(128, 128, 128));
+		canvas.draw_rect(panel).unwrap();
+	}
+}
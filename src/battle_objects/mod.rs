@@ -2,9 +2,19 @@ pub(crate) mod button;
 pub(crate) mod other_player;
 pub(crate) mod buildables;
 pub(crate) mod enemy;
+pub(crate) mod boss;
 pub(crate) mod projectiles;
+pub(crate) mod faction;
 pub(crate) mod coordinates;
 pub(crate) mod ability_plots;
 pub(crate) mod hud;
 pub(crate) mod battle_player;
-pub(crate) mod camera;
\ No newline at end of file
+pub(crate) mod camera;
+pub(crate) mod arena;
+pub(crate) mod terrain;
+pub(crate) mod traps;
+pub(crate) mod turret;
+pub(crate) mod fog;
+pub(crate) mod animation;
+pub(crate) mod particles;
+pub(crate) mod minimap;
@@ -7,4 +7,11 @@ pub struct OtherPlayer{
 	pub facing_vector: f32,
 	pub state: PlayerState,
 	pub health: (u32, u32)
+}
+
+impl OtherPlayer{
+	pub fn vision_range() -> u8{
+		//we aren't told what abilities other players have, so assume they can only see as far as anyone can
+		5
+	}
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+use std::f32::consts::PI;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::GameCoord;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::RenderLayer;
+use crate::utils::random::SeededRng;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleEffect{
+	WallHit,
+	WallDebris,
+	EnemyDeath,
+	LearningSparkle,
+	ProjectileTrail,
+	Healing,
+}
+
+impl ParticleEffect{
+	fn burst_size(&self) -> u32{
+		match self {
+			ParticleEffect::WallHit => 4,
+			ParticleEffect::WallDebris => 12,
+			ParticleEffect::EnemyDeath => 16,
+			ParticleEffect::LearningSparkle => 1,
+			ParticleEffect::ProjectileTrail => 1,
+			ParticleEffect::Healing => 1,
+		}
+	}
+
+	fn lifetime(&self) -> u32{
+		match self {
+			ParticleEffect::WallHit => 8,
+			ParticleEffect::WallDebris => 24,
+			ParticleEffect::EnemyDeath => 20,
+			ParticleEffect::LearningSparkle => 15,
+			ParticleEffect::ProjectileTrail => 6,
+			ParticleEffect::Healing => 20,
+		}
+	}
+
+	fn speed(&self) -> f32{
+		//pixels per tick at launch
+		match self {
+			ParticleEffect::WallHit => 2.5,
+			ParticleEffect::WallDebris => 1.5,
+			ParticleEffect::EnemyDeath => 2.0,
+			ParticleEffect::LearningSparkle => 0.3,
+			ParticleEffect::ProjectileTrail => 0.0,
+			ParticleEffect::Healing => 0.2,
+		}
+	}
+
+	fn rise(&self) -> f32{
+		//sparkles and healing float upward on top of wherever they were thrown
+		match self {
+			ParticleEffect::LearningSparkle => 0.6,
+			ParticleEffect::Healing => 0.8,
+			_ => 0.0,
+		}
+	}
+
+	fn size(&self) -> u32{
+		match self {
+			ParticleEffect::WallDebris => 3,
+			ParticleEffect::EnemyDeath => 3,
+			_ => 2,
+		}
+	}
+
+	fn color(&self) -> Color{
+		match self {
+			ParticleEffect::WallHit => Color::RGB(220, 220, 200),
+			ParticleEffect::WallDebris => Color::RGB(140, 110, 80),
+			ParticleEffect::EnemyDeath => Color::RGB(160, 40, 160),
+			ParticleEffect::LearningSparkle => Color::CYAN,
+			ParticleEffect::ProjectileTrail => Color::RGB(255, 200, 120),
+			ParticleEffect::Healing => Color::GREEN,
+		}
+	}
+
+	fn spread(&self) -> f32{
+		//how far from the emitter a particle can start, in pixels
+		match self {
+			ParticleEffect::LearningSparkle => 8.0,
+			ParticleEffect::Healing => 6.0,
+			ParticleEffect::WallDebris => 6.0,
+			_ => 1.0,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Particle{
+	pos: (f32, f32),
+	velocity: (f32, f32),
+	color: Color,
+	size: u32,
+	life: (u32, u32), //ticks left, ticks it started with
+}
+
+#[derive(Clone)]
+pub struct ParticleSystem{
+	//purely cosmetic, nothing in the simulation ever reads from here
+	pool: Vec<Particle>,
+	free: Vec<usize>, //dead slots in the pool, ready for reuse
+	spawned_this_frame: u32,
+	rng: SeededRng, //kept apart from anything gameplay rolls so effects can't change the outcome of a battle
+}
+
+impl ParticleSystem{
+	pub fn new() -> ParticleSystem{
+		let dead = Particle{ pos: (0.0, 0.0), velocity: (0.0, 0.0), color: Color::BLACK, size: 0, life: (0, 1) };
+		ParticleSystem{
+			pool: vec![dead; ParticleSystem::pool_size()],
+			free: (0..ParticleSystem::pool_size()).rev().collect(),
+			spawned_this_frame: 0,
+			rng: SeededRng::new(0xB00B1E5),
+		}
+	}
+
+	pub fn pool_size() -> usize{
+		512
+	}
+
+	pub fn frame_budget() -> u32{
+		//new particles per tick, anything past this is dropped rather than slowing the frame down
+		96
+	}
+
+	fn drag() -> f32{
+		0.88
+	}
+
+	pub fn emit(&mut self, effect: ParticleEffect, at: GameCoord){
+		for _ in 0..effect.burst_size() {
+			if self.spawned_this_frame >= ParticleSystem::frame_budget() {
+				return;
+			}
+			let Some(slot) = self.free.pop() else { return };
+			let angle = self.rng.next_f32() * 2.0 * PI;
+			let speed = effect.speed() * (0.5 + self.rng.next_f32());
+			let offset_angle = self.rng.next_f32() * 2.0 * PI;
+			let offset = effect.spread() * self.rng.next_f32();
+			self.pool[slot] = Particle{
+				pos: (at.x as f32 + offset_angle.cos() * offset, at.y as f32 + offset_angle.sin() * offset),
+				velocity: (angle.cos() * speed, angle.sin() * speed - effect.rise()),
+				color: effect.color(),
+				size: effect.size(),
+				life: (effect.lifetime(), effect.lifetime()),
+			};
+			self.spawned_this_frame += 1;
+		}
+	}
+
+	pub fn update(&mut self){
+		self.spawned_this_frame = 0;
+		for (index, particle) in self.pool.iter_mut().enumerate() {
+			if particle.life.0 == 0 {
+				continue;
+			}
+			particle.pos.0 += particle.velocity.0;
+			particle.pos.1 += particle.velocity.1;
+			particle.velocity.0 *= ParticleSystem::drag();
+			particle.velocity.1 *= ParticleSystem::drag();
+			particle.life.0 -= 1;
+			if particle.life.0 == 0 {
+				self.free.push(index);
+			}
+		}
+	}
+
+	fn alive(&self) -> impl Iterator<Item = &Particle>{
+		self.pool.iter().filter(|particle| particle.life.0 > 0)
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for ParticleSystem{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Effects
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera = &ctx.camera_state;
+		let canvas_size = canvas.output_size().unwrap();
+		canvas.set_blend_mode(BlendMode::Blend);
+		for particle in self.alive() {
+			let pos = GameCoord{ x: particle.pos.0.round() as i32, y: particle.pos.1.round() as i32 };
+			if !ctx.fog.is_visible(pos.to_grid_coord()) {
+				continue;
+			}
+			//fade out over the last half of its life
+			let alpha = (510 * particle.life.0 / particle.life.1).min(255) as u8;
+			let size = ((camera.scale * particle.size as f32) as u32).max(1);
+			canvas.set_draw_color(Color::RGBA(particle.color.r, particle.color.g, particle.color.b, alpha));
+			canvas.fill_rect(Rect::from_center(pos.to_display_coord(camera.pos, camera.scale, canvas_size), size, size)).unwrap();
+		}
+		canvas.set_blend_mode(BlendMode::None);
+	}
+}
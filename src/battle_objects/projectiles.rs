@@ -1,9 +1,102 @@
-use crate::battle_objects::coordinates::GameCoord;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::battle_objects::faction::Faction;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
 
 #[derive(Clone)]
-pub struct FriendlyProjectile{
+pub struct Projectile{
+	pub faction: Faction,
+	pub pos: GameCoord,
 	pub source_pos: GameCoord,
 	pub target_pos: GameCoord,
 	pub speed: f32,
 	pub damage: i32,
-}
\ No newline at end of file
+	pub max_range: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ProjectileHit{
+	Wall((GameCoord, GameCoord)),
+	Window((GameCoord, GameCoord)),
+	Door((GameCoord, GameCoord)),
+	Button,
+	Turret(GridCoord),
+	Player,
+	OtherPlayer(usize),
+	Enemy(usize),
+	Boss(usize),
+}
+
+impl Projectile {
+	pub fn new(faction: Faction, source_pos: GameCoord, target_pos: GameCoord, damage: i32) -> Projectile {
+		Projectile{
+			faction,
+			pos: source_pos,
+			source_pos,
+			target_pos,
+			speed: Projectile::default_speed(),
+			damage,
+			max_range: Projectile::default_range(),
+		}
+	}
+
+	pub fn default_speed() -> f32 {
+		6.0
+	}
+
+	pub fn default_range() -> f32 {
+		240.0
+	}
+
+	const fn width() -> u32 {
+		4
+	}
+
+	pub fn advance(&mut self) -> (GameCoord, GameCoord) {
+		//returns the segment travelled this tick, so fast projectiles can't skip over thin walls
+		let previous = self.pos;
+		let angle = (self.target_pos.y as f32 - self.source_pos.y as f32).atan2(self.target_pos.x as f32 - self.source_pos.x as f32);
+		let travelled = self.source_pos.pythagorean_distance_to(&self.pos) + self.speed;
+		self.pos = GameCoord{
+			x: self.source_pos.x + (angle.cos() * travelled) as i32,
+			y: self.source_pos.y + (angle.sin() * travelled) as i32,
+		};
+		(previous, self.pos)
+	}
+
+	pub fn is_spent(&self) -> bool {
+		self.source_pos.pythagorean_distance_to(&self.pos) >= self.max_range
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for Projectile{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Effects
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos, Projectile::width()))
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		ctx.fog.is_visible(self.pos.to_grid_coord())
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera = &ctx.camera_state;
+		let projectile_rect = Rect::from_center(
+			self.pos.to_display_coord(camera.pos, camera.scale, canvas.output_size().unwrap()),
+			(camera.scale * Projectile::width() as f32) as u32,
+			(camera.scale * Projectile::width() as f32) as u32
+		);
+		canvas.set_draw_color(match self.faction {
+			Faction::Friendly => Color::CYAN,
+			Faction::Hostile => Color::RGB(255, 64, 64),
+		});
+		canvas.fill_rect(projectile_rect).unwrap();
+	}
+}
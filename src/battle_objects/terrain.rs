@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::RenderLayer;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Terrain{
+	Floor,
+	Mud, //slows anything walking through it
+	Water, //impassable, but can be seen and shot across
+	Rubble, //slightly slow, and too uneven to build on
+	Pit, //impassable, anything pushed in falls to its death
+}
+
+impl Terrain{
+	pub fn all() -> [Terrain; 5]{
+		[Terrain::Floor, Terrain::Mud, Terrain::Water, Terrain::Rubble, Terrain::Pit]
+	}
+
+	pub fn from_name(name: &str) -> Option<Terrain>{
+		Terrain::all().into_iter().find(|terrain| format!("{:?}", terrain) == name)
+	}
+
+	pub fn speed_multiplier(&self) -> f32{
+		match self {
+			Terrain::Floor => 1.0,
+			Terrain::Mud => 0.5,
+			Terrain::Rubble => 0.8,
+			//nothing can step into these, but anything that gets shoved or spawned in climbs back out at its usual pace
+			//anything slower rounds down to standing still for the slowest bodies
+			Terrain::Water | Terrain::Pit => 1.0,
+		}
+	}
+
+	pub fn is_passable(&self) -> bool{
+		!matches!(self, Terrain::Water | Terrain::Pit)
+	}
+
+	pub fn blocks_building(&self) -> bool{
+		matches!(self, Terrain::Rubble)
+	}
+
+	pub fn step_cost(&self) -> f32{
+		//pathing cost of stepping onto a square of this terrain, never below 1.0 so distance stays a valid heuristic
+		match self {
+			Terrain::Floor => 1.0,
+			Terrain::Mud => 2.0,
+			Terrain::Rubble => 1.25,
+			Terrain::Water | Terrain::Pit => f32::MAX,
+		}
+	}
+
+	fn color(&self) -> Option<Color>{
+		match self {
+			Terrain::Floor => None,
+			Terrain::Mud => Some(Color::RGB(72, 48, 24)),
+			Terrain::Water => Some(Color::RGB(24, 48, 128)),
+			Terrain::Rubble => Some(Color::RGB(72, 72, 64)),
+			Terrain::Pit => Some(Color::RGB(0, 0, 0)),
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct TerrainMap{
+	//anything not in the map is plain floor, so the world stays infinite
+	tiles: HashMap<GridCoord, Terrain>,
+}
+
+impl TerrainMap{
+	pub fn new() -> TerrainMap{
+		TerrainMap{ tiles: HashMap::new() }
+	}
+
+	pub fn get(&self, square: GridCoord) -> Terrain{
+		*self.tiles.get(&square).unwrap_or(&Terrain::Floor)
+	}
+
+	pub fn set(&mut self, square: GridCoord, terrain: Terrain){
+		match terrain {
+			Terrain::Floor => self.tiles.remove(&square),
+			_ => self.tiles.insert(square, terrain),
+		};
+	}
+
+	pub fn allows_step(&self, from: GameCoord, to: GameCoord) -> bool{
+		//nothing walks into water or a pit, but anything already in one is always free to climb back out
+		self.get(to.to_grid_coord()).is_passable() || !self.get(from.to_grid_coord()).is_passable()
+	}
+
+	pub fn blocks_building_on(&self, edge: (GameCoord, GameCoord)) -> bool{
+		//walls go on edges, so check the squares on both sides
+		let midpoint = GameCoord{x: (edge.0.x + edge.1.x)/2, y: (edge.0.y + edge.1.y)/2};
+		let half_width = GridCoord::grid_size()/2;
+		let sides = if edge.0.y == edge.1.y {
+			[GameCoord{x: midpoint.x, y: midpoint.y - half_width}, GameCoord{x: midpoint.x, y: midpoint.y + half_width}]
+		} else {
+			[GameCoord{x: midpoint.x - half_width, y: midpoint.y}, GameCoord{x: midpoint.x + half_width, y: midpoint.y}]
+		};
+		sides.iter().any(|side| self.get(side.to_grid_coord()).blocks_building())
+	}
+
+	pub fn tiles(&self) -> impl Iterator<Item = (&GridCoord, &Terrain)>{
+		self.tiles.iter()
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for TerrainMap{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Terrain
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera_pos = ctx.camera_state.pos;
+		let camera_scale = ctx.camera_state.scale;
+		let canvas_size = canvas.output_size().unwrap();
+		let tile_width = (camera_scale * GridCoord::grid_size() as f32) as u32;
+		for (square, terrain) in self.tiles() {
+			let Some(color) = terrain.color() else { continue };
+			let tile_rect = Rect::from_center(
+				square.center().to_display_coord(camera_pos, camera_scale, canvas_size),
+				tile_width,
+				tile_width
+			);
+			canvas.set_draw_color(color);
+			canvas.fill_rect(tile_rect).unwrap();
+			if let Terrain::Pit = terrain {
+				canvas.set_draw_color(Color::RGB(64, 64, 64));
+				canvas.draw_rect(tile_rect).unwrap();
+			}
+		}
+	}
+}
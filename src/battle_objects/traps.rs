@@ -0,0 +1,128 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::coordinates::GridCoord;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrapKind{
+	Spikes, //hurts whatever steps on it
+	Tar, //slows whatever steps on it for a while
+	Alarm, //a tripwire that warns the players where enemies are coming in
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TrapState{
+	Armed,
+	Rearming(u32, u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TrapEffect{
+	Damage(u32),
+	Slow(u32), //ticks to slow for
+	Alarm(GridCoord),
+}
+
+#[derive(Clone, Copy)]
+pub struct Trap{
+	pub pos: GridCoord,
+	pub kind: TrapKind,
+	pub durability: (u32, u32), //triggers left before the trap falls apart
+	pub state: TrapState,
+}
+
+impl Trap{
+	pub fn new(pos: GridCoord, kind: TrapKind) -> Trap{
+		let durability = match kind {
+			TrapKind::Spikes => 5,
+			TrapKind::Tar => 8,
+			TrapKind::Alarm => 3,
+		};
+		Trap{ pos, kind, durability: (durability, durability), state: TrapState::Armed }
+	}
+
+	pub fn rearm_time(&self) -> u32{
+		match self.kind {
+			TrapKind::Spikes => 60,
+			TrapKind::Tar => 30,
+			TrapKind::Alarm => 90,
+		}
+	}
+
+	pub fn is_armed(&self) -> bool{
+		matches!(self.state, TrapState::Armed)
+	}
+
+	pub fn update(&mut self){
+		self.state = match self.state {
+			TrapState::Rearming(curr, max) if curr + 1 >= max => TrapState::Armed,
+			TrapState::Rearming(curr, max) => TrapState::Rearming(curr + 1, max),
+			TrapState::Armed => TrapState::Armed,
+		}
+	}
+
+	pub fn trigger(&mut self) -> Option<TrapEffect>{
+		//returns what happens to whatever set the trap off, or None if it isn't armed
+		if !self.is_armed() || self.durability.0 == 0 {
+			return None;
+		}
+		self.durability.0 -= 1;
+		self.state = TrapState::Rearming(0, self.rearm_time());
+		Some(match self.kind {
+			TrapKind::Spikes => TrapEffect::Damage(40),
+			TrapKind::Tar => TrapEffect::Slow(90),
+			TrapKind::Alarm => TrapEffect::Alarm(self.pos),
+		})
+	}
+
+	pub fn is_broken(&self) -> bool{
+		self.durability.0 == 0
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for Trap{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Floor
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.center().y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos.center(), GridCoord::grid_size() as u32))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera = &ctx.camera_state;
+		let center = self.pos.center().to_display_coord(camera.pos, camera.scale, canvas.output_size().unwrap());
+		let size = (camera.scale * (GridCoord::grid_size() - 4) as f32) as u32;
+		let trap_rect = Rect::from_center(center, size, size);
+		//rearming traps are drawn faded out
+		let alpha = if self.is_armed() { 255 } else { 96 };
+		canvas.set_blend_mode(BlendMode::Blend);
+		match self.kind {
+			TrapKind::Spikes => {
+				canvas.set_draw_color(Color::RGBA(160, 160, 160, alpha));
+				canvas.draw_rect(trap_rect).unwrap();
+				canvas.draw_line(trap_rect.top_left(), trap_rect.bottom_right()).unwrap();
+				canvas.draw_line(trap_rect.top_right(), trap_rect.bottom_left()).unwrap();
+			},
+			TrapKind::Tar => {
+				canvas.set_draw_color(Color::RGBA(24, 16, 8, alpha));
+				canvas.fill_rect(trap_rect).unwrap();
+			},
+			TrapKind::Alarm => {
+				canvas.set_draw_color(Color::RGBA(255, 220, 0, alpha));
+				canvas.draw_line(
+					Point::new(trap_rect.left(), center.y()),
+					Point::new(trap_rect.right(), center.y())
+				).unwrap();
+			},
+		}
+		canvas.set_blend_mode(BlendMode::None);
+	}
+}
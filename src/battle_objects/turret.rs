@@ -0,0 +1,119 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::buildables::WallStore;
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::battle_objects::enemy::Enemy;
+use crate::battle_objects::faction::Faction;
+use crate::battle_objects::projectiles::Projectile;
+use crate::screens::battle::{BattleContext, BattleRenderable};
+use crate::screens::render_queue::{RenderLayer, Bounds, box_around};
+use crate::utils::visibility::has_line_of_sight;
+
+#[derive(Clone, Copy)]
+pub struct Turret{
+	pub pos: GridCoord,
+	pub health: (u32, u32),
+	pub ammo: (u32, u32), //shots left before someone has to come and repair it
+	pub cooldown: u32,
+	pub aim_angle: f32, //radians, only used to draw the barrel
+}
+
+impl Turret{
+	pub fn new(pos: GridCoord) -> Turret{
+		Turret{ pos, health: (80, 80), ammo: (20, 20), cooldown: 0, aim_angle: 0.0 }
+	}
+
+	pub fn range() -> f32{
+		6.0
+	}
+
+	pub fn fire_cooldown() -> u32{
+		20
+	}
+
+	pub fn damage() -> i32{
+		15
+	}
+
+	pub fn refill_amount() -> u32{
+		10
+	}
+
+	pub fn repair_amount() -> u32{
+		20
+	}
+
+	fn find_target(&self, enemies: &Vec<Enemy>, walls: &WallStore) -> Option<GameCoord>{
+		//the nearest enemy in range that isn't hiding behind a wall
+		let center = self.pos.center();
+		enemies.iter()
+				.filter(|enemy| enemy.pos.to_grid_coord().pythagorean_distance_to(&self.pos) <= Turret::range())
+				.filter(|enemy| has_line_of_sight(center, enemy.pos, walls, None))
+				.min_by(|enemy1, enemy2| {
+					let distance1 = enemy1.pos.pythagorean_distance_to(&center);
+					let distance2 = enemy2.pos.pythagorean_distance_to(&center);
+					distance1.partial_cmp(&distance2).unwrap()
+				})
+				.map(|enemy| enemy.pos)
+	}
+
+	pub fn update(&mut self, enemies: &Vec<Enemy>, walls: &WallStore) -> Option<Projectile>{
+		self.cooldown = self.cooldown.saturating_sub(1);
+		if self.cooldown > 0 || self.ammo.0 == 0 {
+			return None;
+		}
+		let target = self.find_target(enemies, walls)?;
+		let center = self.pos.center();
+		self.aim_angle = ((target.y - center.y) as f32).atan2((target.x - center.x) as f32);
+		self.ammo.0 -= 1;
+		self.cooldown = Turret::fire_cooldown();
+		Some(Projectile::new(Faction::Friendly, center, target, Turret::damage()))
+	}
+
+	pub fn repair(&mut self){
+		self.ammo.0 = (self.ammo.0 + Turret::refill_amount()).min(self.ammo.1);
+		self.health.0 = (self.health.0 + Turret::repair_amount()).min(self.health.1);
+	}
+}
+
+impl<T: RenderTarget> BattleRenderable<T> for Turret{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Floor
+	}
+
+	fn sort_key(&self) -> i32{
+		self.pos.center().y
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		Some(box_around(self.pos.center(), 2 * GridCoord::grid_size() as u32))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		let camera = &ctx.camera_state;
+		let center = self.pos.center().to_display_coord(camera.pos, camera.scale, canvas.output_size().unwrap());
+		let size = (camera.scale * (GridCoord::grid_size() - 6) as f32) as u32;
+		let base_rect = Rect::from_center(center, size, size);
+		canvas.set_draw_color(Color::RGB(0, 128, 128));
+		canvas.fill_rect(base_rect).unwrap();
+		//the barrel points at whatever it last shot at
+		let barrel_length = camera.scale * GridCoord::grid_size() as f32 * 0.6;
+		let barrel_end = Point::new(
+			center.x() + (self.aim_angle.cos() * barrel_length) as i32,
+			center.y() + (self.aim_angle.sin() * barrel_length) as i32
+		);
+		canvas.set_draw_color(Color::RGB(200, 200, 200));
+		canvas.draw_line(center, barrel_end).unwrap();
+		//ammo gauge along the bottom, red once it's empty
+		let gauge_width = (size as f32 * self.ammo.0 as f32 / self.ammo.1 as f32) as u32;
+		if gauge_width > 0 {
+			canvas.set_draw_color(Color::YELLOW);
+			canvas.fill_rect(Rect::new(base_rect.left(), base_rect.bottom(), gauge_width, 2)).unwrap();
+		} else {
+			canvas.set_draw_color(Color::RED);
+			canvas.fill_rect(Rect::new(base_rect.left(), base_rect.bottom(), size, 2)).unwrap();
+		}
+	}
+}
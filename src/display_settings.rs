@@ -0,0 +1,95 @@
+use sdl2::video::{FullscreenType, Window};
+use sdl2::VideoSubsystem;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowMode{
+	Windowed,
+	Fullscreen, //takes over the display at our resolution
+	Borderless, //fullscreen at whatever the desktop is already running at
+}
+
+impl WindowMode{
+	fn fullscreen_type(&self) -> FullscreenType{
+		match self {
+			WindowMode::Windowed => FullscreenType::Off,
+			WindowMode::Fullscreen => FullscreenType::True,
+			WindowMode::Borderless => FullscreenType::Desktop,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct DisplaySettings{
+	pub resolution: (u32, u32),
+	pub window_mode: WindowMode,
+	pub fullscreen_mode: WindowMode, //what toggling fullscreen switches to, so borderless players stay borderless
+}
+
+impl DisplaySettings{
+	pub fn new() -> DisplaySettings{
+		DisplaySettings{
+			resolution: (1080, 720),
+			window_mode: WindowMode::Windowed,
+			fullscreen_mode: WindowMode::Borderless,
+		}
+	}
+
+	pub fn from_args(args: impl Iterator<Item = String>) -> DisplaySettings{
+		//--resolution 1920x1080, --fullscreen, --borderless, --windowed
+		let mut settings = DisplaySettings::new();
+		let mut args = args.peekable();
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--resolution" => {
+					let parsed = args.next().and_then(|value| {
+						let (width, height) = value.split_once('x')?;
+						Some((width.parse().ok()?, height.parse().ok()?))
+					});
+					match parsed {
+						Some(resolution) => settings.resolution = resolution,
+						None => println!("Expected --resolution WIDTHxHEIGHT, keeping {:?}", settings.resolution),
+					}
+				},
+				"--fullscreen" => {
+					settings.window_mode = WindowMode::Fullscreen;
+					settings.fullscreen_mode = WindowMode::Fullscreen;
+				},
+				"--borderless" => {
+					settings.window_mode = WindowMode::Borderless;
+					settings.fullscreen_mode = WindowMode::Borderless;
+				},
+				"--windowed" => settings.window_mode = WindowMode::Windowed,
+				_ => (),
+			}
+		}
+		settings
+	}
+
+	pub fn build_window(&self, video_subsystem: &VideoSubsystem) -> Window{
+		let mut builder = video_subsystem.window("Button Game", self.resolution.0, self.resolution.1);
+		builder.position_centered().resizable();
+		match self.window_mode {
+			WindowMode::Windowed => (),
+			WindowMode::Fullscreen => { builder.fullscreen(); },
+			WindowMode::Borderless => { builder.fullscreen_desktop(); },
+		}
+		builder.build().expect("Failed to create window")
+	}
+
+	pub fn toggle_fullscreen(&mut self, window: &mut Window){
+		self.window_mode = match self.window_mode {
+			WindowMode::Windowed => self.fullscreen_mode,
+			_ => WindowMode::Windowed,
+		};
+		if let Err(error) = window.set_fullscreen(self.window_mode.fullscreen_type()) {
+			println!("Unable to switch to {:?}: {}", self.window_mode, error);
+		}
+	}
+
+	pub fn resized(&mut self, width: i32, height: i32){
+		//only windowed sizes are worth remembering, fullscreen ones come from the display
+		if self.window_mode == WindowMode::Windowed {
+			self.resolution = (width.max(1) as u32, height.max(1) as u32);
+		}
+	}
+}
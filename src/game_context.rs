@@ -1,14 +1,17 @@
-use sdl2::render::{WindowCanvas, Texture};
-use crate::game_context::GameContext::{Battle, StartScreen};
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::game_context::GameContext::{Battle, Editor, StartScreen};
 use crate::screens::start::render_start_screen;
 use crate::screens::battle::{BattleContext, render_battle};
 use crate::screens::start::StartScreenContext;
+use crate::screens::editor::{EditorContext, render_editor};
 use crate::input::{InputState};
 use crate::sound_manager::SoundManager;
 
 pub struct GameObject{
 	pub phase: GameContext,
 	pub player: Option<Player>,
+	pub viewport: (u32, u32), //current size of the window in pixels
 }
 
 impl GameObject{
@@ -19,14 +22,18 @@ impl GameObject{
 			},
 			StartScreen(_start_context) =>{
 				StartScreenContext::handle_tick(self, input_state, my_sound_manager);
+			},
+			Editor(_editor_context) =>{
+				EditorContext::handle_tick(self, input_state, my_sound_manager);
 			}
 		}
 	}
 
-	pub fn render(&self, canvas: &mut WindowCanvas, background_texture: &Texture){
+	pub fn render<T: RenderTarget>(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>){
 		match &self.phase{
-			Battle(battle) => render_battle(canvas, background_texture, &battle),
-			StartScreen(ctx) => render_start_screen(canvas, background_texture, &ctx),
+			Battle(battle) => render_battle(canvas, assets, &battle),
+			StartScreen(ctx) => render_start_screen(canvas, assets, &ctx),
+			Editor(ctx) => render_editor(canvas, assets, &ctx),
 		}
 	}
 }
@@ -34,6 +41,7 @@ impl GameObject{
 pub enum GameContext{
 	StartScreen(StartScreenContext),
 	Battle(BattleContext),
+	Editor(EditorContext),
 }
 
 #[derive(Clone, Copy)]
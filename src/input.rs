@@ -115,6 +115,10 @@ pub fn read_input_event(input: &mut InputState, controller_settings: &Controller
 				&Keycode::E => { input.left_shoulder = true },
 				&Keycode::U => { input.right_shoulder = true },
 				&Keycode::Return => { input.btn_start = true },
+				&Keycode::Tab => { input.btn_back = true },
+				&Keycode::Equals | &Keycode::KpPlus => { input.right_y_pos = -i16::MAX },
+				&Keycode::Minus | &Keycode::KpMinus => { input.right_y_pos = i16::MAX },
+				&Keycode::F => { input.right_stick = true },
 				_ => ()
 			}
 		},
@@ -132,6 +136,9 @@ pub fn read_input_event(input: &mut InputState, controller_settings: &Controller
 				&Keycode::E => { input.left_shoulder = false },
 				&Keycode::U => { input.right_shoulder = false },
 				&Keycode::Return => { input.btn_start = false },
+				&Keycode::Tab => { input.btn_back = false },
+				&Keycode::Equals | &Keycode::KpPlus | &Keycode::Minus | &Keycode::KpMinus => { input.right_y_pos = 0 },
+				&Keycode::F => { input.right_stick = false },
 				_ => ()
 			};
 		},
@@ -6,12 +6,16 @@ mod sound_manager;
 mod game_context;
 mod battle_objects;
 mod utils;
+mod asset_cache;
+mod display_settings;
 
 use std::time::{Duration, Instant};
-use sdl2::image::{LoadTexture};
-
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
 use input::{InputState, ControllerSettings, read_input_event};
 use sound_manager::SoundManager;
+use asset_cache::AssetCache;
+use display_settings::DisplaySettings;
 use game_context::{GameContext, GameObject, Player};
 use crate::screens::start::StartScreenContext;
 
@@ -26,19 +30,16 @@ fn main() {
 	let mut my_sound_manager = SoundManager::new();
 	let _controller = controller_subsystem.open(0);
 	let controller_settings = ControllerSettings::new();
-	let window = video_subsystem.window("Button Game", 1080, 720)
-		.position_centered()
-		.build()
-		.expect("Failed to create window");
+	let mut display_settings = DisplaySettings::from_args(std::env::args().skip(1));
+	let window = display_settings.build_window(&video_subsystem);
 	let mut canvas = window.into_canvas()
 		.software()
 		.build()
 		.expect("Failed to create canvas from window");
 
 	let texture_creator = canvas.texture_creator();
-
-	let background_texture = texture_creator.load_texture("assets/images/button_game_splash_art.png")
-		.expect("Unable to create background texture.");
+	let ttf_context = sdl2::ttf::init().expect("Unable to initialize sdl ttf context");
+	let mut assets = AssetCache::new(&texture_creator, &ttf_context);
 
 	let mut events = sdl_context.event_pump()
 		.expect("Unable to initialize sdl event pump");
@@ -47,14 +48,24 @@ fn main() {
 
 	let mut game_obj = GameObject{
 		phase: GameContext::StartScreen(StartScreenContext::new()),
-		player: Some(Player{})
-
+		player: Some(Player{}),
+		viewport: canvas.output_size().unwrap(),
 	};
 
 	'mainloop: loop {
 		let frame_start = Instant::now();
 		for event in events.poll_iter() {
-			read_input_event(&mut input_state, &controller_settings, &event);
+			match event {
+				Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+					display_settings.resized(width, height);
+					game_obj.viewport = canvas.output_size().unwrap();
+				},
+				Event::KeyDown { keycode: Some(Keycode::F11), repeat: false, .. } => {
+					display_settings.toggle_fullscreen(canvas.window_mut());
+					game_obj.viewport = canvas.output_size().unwrap();
+				},
+				_ => read_input_event(&mut input_state, &controller_settings, &event),
+			}
 		}
 		//println!("{:?}", input_state);
 		if input_state.shutdown{
@@ -62,7 +73,7 @@ fn main() {
 		}
 
 		game_obj.handle_tick(&input_state, &mut my_sound_manager);
-		game_obj.render(&mut canvas, &background_texture);
+		game_obj.render(&mut canvas, &mut assets);
 
 		// Sleep if we finished this frame early, so we lock to the desired framerate
 		let frame_duration = frame_start.elapsed();
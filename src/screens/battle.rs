@@ -1,24 +1,43 @@
-use std::collections::{HashSet};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+use std::time::{SystemTime, UNIX_EPOCH};
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
 
+use crate::asset_cache::AssetCache;
 use crate::battle_objects::ability_plots::AbilityPlot;
-use crate::battle_objects::battle_player::Ability::{Armor, Blank, Build, ButtonPress, Heal, MeleeAttack, RangeAttack, Repair, Vision};
+use crate::battle_objects::animation::{AnimationKey, Animator};
+use crate::battle_objects::battle_player::Ability::{Blank, Build, MeleeAttack, RangeAttack, Repair, Vision, Armor, ButtonPress, Heal};
 use crate::battle_objects::battle_player::{BattlePlayerContext, PlayerState};
-use crate::battle_objects::buildables::{Wall, Window};
-use crate::battle_objects::button::Button;
-use crate::battle_objects::camera::CameraState;
+use crate::battle_objects::buildables::{BuildSite, Buildable, Door, DoorStore, Wall, WallChange, WallMaterial, WallStore, Window};
+use crate::battle_objects::boss::{Boss, BossAction};
+use crate::battle_objects::arena::ArenaLayout;
+use crate::battle_objects::button::{Button, ButtonState};
+use crate::battle_objects::camera::{CameraMode, CameraState};
 use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
-use crate::battle_objects::enemy::{Enemy, EnemyBehavior};
+use crate::battle_objects::enemy::{Enemy, EnemyBehavior, EnemyKind};
+use crate::battle_objects::faction::Faction;
+use crate::battle_objects::fog::FogOfWar;
 use crate::battle_objects::hud::Hud;
+use crate::battle_objects::minimap::Minimap;
+use crate::battle_objects::particles::{ParticleEffect, ParticleSystem};
 use crate::battle_objects::other_player::OtherPlayer;
-use crate::battle_objects::projectiles::FriendlyProjectile;
+use crate::battle_objects::projectiles::{Projectile, ProjectileHit};
+use crate::battle_objects::terrain::{Terrain, TerrainMap};
+use crate::battle_objects::traps::{Trap, TrapEffect};
+use crate::battle_objects::turret::Turret;
 use crate::game_context::{GameContext, GameObject};
 use crate::input::{get_player_intent_vector, InputState};
+use crate::screens::render_queue::{Bounds, RenderLayer, RenderQueue};
 use crate::sound_manager::SoundManager;
-use crate::utils::collisions::line_to_line_intersect;
-use crate::utils::pathing::path_to;
+use crate::utils::collisions::{line_to_line_intersect, line_to_square_intersect, square_to_square_overlap};
+use crate::utils::arena_generation::generate_arena;
+use crate::utils::edge_store::{normalize_edge, shared_edge, Edge};
+use crate::utils::pathing::{is_reachable, path_to};
+use crate::utils::layout::{Anchor, Layout};
+use crate::utils::render_utils::render_text;
+use crate::utils::visibility::{field_of_view, has_line_of_sight, ViewCone};
 
 #[derive(Clone)]
 pub enum BattleState{
@@ -33,228 +52,716 @@ pub struct BattleContext{
 	pub player: BattlePlayerContext,
 	pub round_time: u32, //frame count for the battle context
 	pub camera_state: CameraState,
+	pub fog: FogOfWar,
+	pub minimap: Minimap,
+	pub particles: ParticleSystem,
+	pub terrain: TerrainMap,
 	pub button: Button,
 	pub other_players: Vec<OtherPlayer>,
-	pub walls:  Vec<Wall>,
+	pub walls: WallStore,
+	pub doors: DoorStore,
+	pub traps: HashMap<GridCoord, Trap>,
+	pub turrets: HashMap<GridCoord, Turret>,
+	pub alarm: Option<(GridCoord, u32)>, //where the last tripwire went off, and how much longer to flag it
+	pub build_feedback: Option<(String, u32)>, //why the last build was refused, and how much longer to show it
+	pub build_preview: Option<Result<(), String>>, //whether the build being placed can go where it's aimed, checked once a tick
+	pub wall_changes: Vec<WallChange>,
 	pub windows: Vec<Window>,
-	pub friendly_projectiles: Vec<FriendlyProjectile>,
+	pub projectiles: Vec<Projectile>,
 	pub enemies: Vec<Enemy>,
+	pub bosses: Vec<Boss>,
+	pub boss_spawn_point: GridCoord,
 	pub ability_plots : Vec<AbilityPlot>
 }
 
 impl BattleContext{
 	pub fn new() -> BattleContext{
-		BattleContext{
+		BattleContext::from_layout(&ArenaLayout::classic())
+	}
+
+	pub fn from_layout(layout: &ArenaLayout) -> BattleContext{
+		let mut context = BattleContext{
 			state: BattleState::Starting,//TODO change this to starting once we have state transitions
 			round_time: 0,
 			player: BattlePlayerContext{
 				facing_vector: 0.0,
 				state: PlayerState::Standing,
 				base_vision_range: 5,
+				health: (100, 100),
 				ability_primary: Blank,
 				ability_secondary: Blank,
-				game_coord: GameCoord{ x:150, y:-150 },
-				snapped_facing_vector: Direction::East
+				game_coord: layout.player_start,
+				snapped_facing_vector: Direction::East,
+				build_selection: Buildable::Wall,
+				materials: 10,
+				animation: Animator::new(AnimationKey::PlayerIdle),
 			},
-			button: Button::new(),
+			button: Button{pos: layout.button, state: ButtonState::NeverPressed},
 			camera_state: CameraState::new(),
-			walls: Vec::new(),
-			windows: Vec::new(),
+			fog: FogOfWar::new(),
+			minimap: Minimap::new(),
+			particles: ParticleSystem::new(),
+			terrain: layout.terrain.clone(),
+			walls: WallStore::from_items(layout.walls.iter().copied()),
+			doors: DoorStore::new(),
+			traps: HashMap::new(),
+			turrets: HashMap::new(),
+			alarm: None,
+			build_feedback: None,
+			build_preview: None,
+			wall_changes: Vec::new(),
+			windows: layout.windows.clone(),
 			other_players: Vec::new(),
-			friendly_projectiles: Vec::new(),
-			enemies: vec![
-				Enemy{ pos: GridCoord {x:10, y:10}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)},
-				Enemy{ pos: GridCoord {x:10, y:-10}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)},
-				Enemy{ pos: GridCoord {x:-10, y:10}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)},
-				Enemy{ pos: GridCoord {x:-10, y:-10}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)},
-				Enemy{ pos: GridCoord {x:12, y:12}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)},
-				Enemy{ pos: GridCoord {x:9, y:11}.center(), snapped_facing_vector: Direction::North, behavior: EnemyBehavior::Idle, health: (100, 100)}
-			],
-			ability_plots: vec![
-				AbilityPlot{pos:GridCoord{x:-1, y:-2}, ability:MeleeAttack},
-				AbilityPlot{pos:GridCoord{x:1,  y:-2}, ability:Armor},
-				AbilityPlot{pos:GridCoord{x:2,  y:-1}, ability:RangeAttack},
-				AbilityPlot{pos:GridCoord{x:2,  y:1}, ability:Vision},
-				AbilityPlot{pos:GridCoord{x:1,  y:2}, ability:Build},
-				AbilityPlot{pos:GridCoord{x:-1, y:2}, ability:Repair},
-				AbilityPlot{pos:GridCoord{x:-2, y:-1}, ability:ButtonPress},
-				AbilityPlot{pos:GridCoord{x:-2, y:1}, ability:Heal}
-			],
-		}
+			projectiles: Vec::new(),
+			enemies: layout.spawn_points
+					.iter()
+					.map(|spawn| Enemy::new(spawn.pos.center(), spawn.kind))
+					.collect(),
+			bosses: Vec::new(),
+			boss_spawn_point: layout.boss_spawn_point(),
+			ability_plots: layout.ability_plots.clone(),
+		};
+		context.update_fog();
+		context
 	}
+
 	pub fn from_game_object(_game_object: &GameObject) -> BattleContext{
-		BattleContext::new()
-		//hydrate from save state if one exists
+		//TODO hydrate from save state if one exists
+		let seed = SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map(|elapsed| elapsed.as_nanos() as u64)
+				.unwrap_or(0);
+		println!("Generating arena from seed {}", seed);
+		BattleContext::from_layout(&generate_arena(seed))
 	}
 
 	pub fn get_visible_squares(&self) -> HashSet<GridCoord>{
+		//everything anyone on the team can see between them
 		let player = &self.player;
-		let player_square = player.game_coord.to_grid_coord();
-		let center_square = GridCoord{x:0, y:0};
-		let mut visible_squares = vec![center_square];
-		for y in 1..=player.get_vision_range() as i32{
-			visible_squares.push(center_square.to_north(y));
-			for x in 1..=y{
-				visible_squares.push(center_square.offset((-x, -y)));
-				visible_squares.push(center_square.offset((x, -y)));
+		let mut visible = field_of_view(
+			player.game_coord.to_grid_coord(),
+			player.get_vision_range(),
+			Some(ViewCone{ facing: player.facing_vector, half_angle: 0.25*PI }),
+			&self.walls
+		);
+		for other in &self.other_players{
+			visible.extend(field_of_view(
+				other.grid_coord,
+				OtherPlayer::vision_range(),
+				Some(ViewCone{ facing: other.facing_vector, half_angle: 0.25*PI }),
+				&self.walls
+			));
+		}
+		visible
+	}
+
+	pub fn update_animations(&mut self){
+		self.player.animation.play(self.player.state.animation());
+		for enemy in &mut self.enemies {
+			enemy.animation.play(enemy.behavior.animation());
+		}
+	}
+
+	pub fn update_particles(&mut self){
+		//ongoing effects get a few new particles every tick, one-off bursts are emitted where they happen
+		if let PlayerState::Learning(_, _, _) = self.player.state {
+			let player_square = self.player.game_coord.to_grid_coord();
+			if let Some(plot) = self.ability_plots.iter().find(|plot| plot.pos == player_square) {
+				self.particles.emit(ParticleEffect::LearningSparkle, plot.pos.center());
 			}
 		}
+		if let PlayerState::Healing(_, _) = self.player.state {
+			self.particles.emit(ParticleEffect::Healing, self.player.game_coord);
+		}
+		for projectile in &self.projectiles {
+			self.particles.emit(ParticleEffect::ProjectileTrail, projectile.pos);
+		}
+		self.particles.update();
+	}
 
-		//add all the other squares directly adjacent of diagonal to the center square
-		visible_squares.push(center_square.to_west(1));
-		visible_squares.push(center_square.to_east(1));
-		visible_squares.push(center_square.to_south(1));
-		visible_squares.push(center_square.offset((-1, 1)));
-		visible_squares.push(center_square.offset((1, 1)));
+	pub fn update_fog(&mut self){
+		let visible = self.get_visible_squares();
+		self.fog.update(visible);
+	}
 
-		match player.snapped_facing_vector{
-			Direction::North => (),
-			Direction::South => {
-				//flip all the y values
-				visible_squares = visible_squares.iter().map(|coord| GridCoord{x: coord.x, y: -coord.y}).collect();
+	pub fn get_learning_time(&self) -> u32{
+		30
+	}
+
+	pub fn get_boss_arrival_time(&self) -> u32{
+		1800
+	}
+
+	pub fn get_boss_spawn_point(&self) -> GameCoord{
+		self.boss_spawn_point.center()
+	}
+
+	pub fn apply_boss_action(&mut self, action: BossAction){
+		match action {
+			BossAction::SpawnMinions(positions) => {
+				for pos in positions {
+					self.enemies.push(Enemy::new(pos, EnemyKind::Melee));
+				}
+			},
+			BossAction::SmashWalls(center, radius) => {
+				//the closer we are to the smash, the harder the screen shakes
+				let distance = center.pythagorean_distance_to(&self.player.game_coord);
+				self.camera_state.add_trauma((0.8 - distance / 400.0).max(0.0));
+				let reach = radius / GridCoord::grid_size() + 1;
+				let smashed_walls: Vec<(GameCoord, GameCoord)> = self.walls
+						.near(center.to_grid_coord(), reach)
+						.into_iter()
+						.filter(|wall| {
+							let midpoint = GameCoord{x: (wall.endpoints.0.x + wall.endpoints.1.x)/2, y: (wall.endpoints.0.y + wall.endpoints.1.y)/2};
+							midpoint.pythagorean_distance_to(&center) <= radius as f32
+						})
+						.map(|wall| wall.endpoints)
+						.collect();
+				let smashed_doors: Vec<(GameCoord, GameCoord)> = self.doors
+						.near(center.to_grid_coord(), reach)
+						.into_iter()
+						.filter(|door| {
+							let midpoint = GameCoord{x: (door.endpoints.0.x + door.endpoints.1.x)/2, y: (door.endpoints.0.y + door.endpoints.1.y)/2};
+							midpoint.pythagorean_distance_to(&center) <= radius as f32
+						})
+						.map(|door| door.endpoints)
+						.collect();
+				for endpoints in smashed_walls {
+					self.remove_wall(endpoints);
+				}
+				for endpoints in smashed_doors {
+					self.remove_door(endpoints);
+				}
 			},
-			Direction::West => {
-				//y becomes x, x becomes y
-				visible_squares = visible_squares.iter().map(|coord| GridCoord{x: coord.y, y: coord.x}).collect();
+			BossAction::Trample(center, damage) => {
+				let boss_body = (center.into(), Boss::width());
+				if square_to_square_overlap((self.player.game_coord.into(), BattlePlayerContext::width()), boss_body).is_some() {
+					self.player.health.0 = self.player.health.0.saturating_sub(damage);
+					self.camera_state.add_trauma(damage as f32 / 40.0);
+				}
+				for other in &mut self.other_players {
+					if square_to_square_overlap((other.grid_coord.center().into(), BattlePlayerContext::width()), boss_body).is_some() {
+						other.health.0 = other.health.0.saturating_sub(damage as u32);
+					}
+				}
 			},
-			Direction::East => {
-				//y becomes -x, x becomes y
-				visible_squares = visible_squares.iter().map(|coord| GridCoord{x: -coord.y, y: coord.x}).collect();
-			}
 		}
-		//add player grid coordinates to all the visible squares
-		visible_squares =  visible_squares
+	}
+
+	pub fn get_enemy_perception(&self) -> Vec<bool>{
+		//which enemies currently know where the player is, indexed the same as self.enemies
+		let player_square = self.player.game_coord.to_grid_coord();
+		let player_is_noisy = !matches!(self.player.state, PlayerState::Standing);
+		let mut perceived: Vec<bool> = self.enemies
 				.iter()
-				.map(|coord| GridCoord{x: coord.x + player_square.x, y: coord.y + player_square.y})
+				.map(|enemy| enemy.perceives(player_square, player_is_noisy, &self.walls))
 				.collect();
+		//alerted enemies call out to anyone within earshot, who can pass the alert along in turn
+		let mut alert_spread = true;
+		while alert_spread {
+			alert_spread = false;
+			for listener_index in 0..self.enemies.len(){
+				if perceived[listener_index] {
+					continue;
+				}
+				let listener_pos = self.enemies[listener_index].pos.to_grid_coord();
+				let heard_alert = self.enemies
+						.iter()
+						.enumerate()
+						.filter(|(alerter_index, _)| perceived[*alerter_index])
+						.any(|(_, alerter)| alerter.pos.to_grid_coord().pythagorean_distance_to(&listener_pos) <= Enemy::alert_range());
+				if heard_alert {
+					perceived[listener_index] = true;
+					alert_spread = true;
+				}
+			}
+		}
+		perceived
+	}
 
-		//get all walls that border the visible squares
-		let all_corners = visible_squares
+	fn can_be_shoved(&self, from: GameCoord, to: GameCoord, faction: Faction) -> bool{
+		//pushes happen after bodies are snapped off the walls, so they have to respect walls, doors and terrain themselves
+		let (from_square, to_square) = (from.to_grid_coord(), to.to_grid_coord());
+		if from_square == to_square {
+			return true;
+		}
+		let terrain = self.terrain.get(to_square);
+		//enemies shoved into a pit fall in, which is the whole point of one
+		let lands = terrain.is_passable() || (faction == Faction::Hostile && terrain == Terrain::Pit);
+		lands
+				&& shared_edge(from_square, to_square).is_some()
+				&& !self.walls.is_blocking(from_square, to_square)
+				&& !self.doors.is_blocking_for(from_square, to_square, faction)
+	}
+
+	fn shove_player(&mut self, (dx, dy): (i32, i32)){
+		let pushed = GameCoord{x: self.player.game_coord.x + dx, y: self.player.game_coord.y + dy};
+		if self.can_be_shoved(self.player.game_coord, pushed, Faction::Friendly) {
+			self.player.game_coord = pushed;
+		}
+	}
+
+	fn shove_enemy(&mut self, index: usize, (dx, dy): (i32, i32)){
+		let pushed = GameCoord{x: self.enemies[index].pos.x + dx, y: self.enemies[index].pos.y + dy};
+		if self.can_be_shoved(self.enemies[index].pos, pushed, Faction::Hostile) {
+			self.enemies[index].pos = pushed;
+		}
+	}
+
+	pub fn resolve_body_collisions(&mut self){
+		//players are never pushed by enemies, so they can body-block them at chokepoints
+		let other_player_squares: Vec<((i32, i32), u32)> = self.other_players
 				.iter()
-				.flat_map(|coord| vec![
-					coord.top_left(),
-					coord.top_right(),
-					coord.bottom_left(),
-					coord.bottom_right()
-				])
-				.collect::<HashSet<GameCoord>>();
-		let relevant_walls = self.walls.iter().filter(|wall| {
-			let wall_corners = vec![wall.endpoints.0, wall.endpoints.1];
-			wall_corners.iter().any(|corner| all_corners.contains(corner))
-		});
-		let start_point:(i32, i32) = match player.snapped_facing_vector{
-			Direction::North => (player_square.center().x, player_square.center().y - GridCoord::grid_size()*0.4 as i32),
-			Direction::South => (player_square.center().x, player_square.center().y + GridCoord::grid_size()*0.4 as i32),
-			Direction::West => (player_square.center().x - GridCoord::grid_size()*0.4 as i32, player_square.center().y),
-			Direction::East => (player_square.center().x + GridCoord::grid_size()*0.4 as i32, player_square.center().y),
+				.map(|other| (other.grid_coord.center().into(), BattlePlayerContext::width()))
+				.collect();
+		for other_square in &other_player_squares {
+			let player_square = (self.player.game_coord.into(), BattlePlayerContext::width());
+			if let Some(push) = square_to_square_overlap(player_square, *other_square) {
+				self.shove_player(push);
+			}
+		}
+		//bosses are too heavy for the player to shove around
+		let boss_squares: Vec<((i32, i32), u32)> = self.bosses.iter().map(|boss| (boss.pos.into(), Boss::width())).collect();
+		for boss_square in &boss_squares {
+			let player_square = (self.player.game_coord.into(), BattlePlayerContext::width());
+			if let Some(push) = square_to_square_overlap(player_square, *boss_square) {
+				self.shove_player(push);
+			}
+		}
+		//turrets are bolted down, nobody walks through them
+		let turret_squares: Vec<((i32, i32), u32)> = self.turrets
+				.keys()
+				.map(|square| (square.center().into(), GridCoord::grid_size() as u32))
+				.collect();
+		for turret_square in &turret_squares {
+			let player_square = (self.player.game_coord.into(), BattlePlayerContext::width());
+			if let Some(push) = square_to_square_overlap(player_square, *turret_square) {
+				self.shove_player(push);
+			}
+		}
+		let mut blocking_squares = other_player_squares;
+		blocking_squares.push((self.player.game_coord.into(), BattlePlayerContext::width()));
+		blocking_squares.extend(boss_squares);
+		blocking_squares.extend(turret_squares);
+
+		for first_index in 0..self.enemies.len(){
+			for second_index in (first_index + 1)..self.enemies.len(){
+				let first_square = (self.enemies[first_index].pos.into(), Enemy::width());
+				let second_square = (self.enemies[second_index].pos.into(), Enemy::width());
+				if let Some((dx, dy)) = square_to_square_overlap(first_square, second_square) {
+					//split the push between both enemies so neither one can shove the other through a crowd
+					self.shove_enemy(first_index, (dx - dx / 2, dy - dy / 2));
+					self.shove_enemy(second_index, (-(dx / 2), -(dy / 2)));
+				}
+			}
+			for blocking_square in &blocking_squares {
+				let enemy_square = (self.enemies[first_index].pos.into(), Enemy::width());
+				if let Some(push) = square_to_square_overlap(enemy_square, *blocking_square) {
+					self.shove_enemy(first_index, push);
+				}
+			}
+		}
+	}
+
+	pub fn damage_wall(&mut self, endpoints: (GameCoord, GameCoord), damage: usize){
+		let Some(wall) = self.walls.get_mut(endpoints) else { return };
+		wall.health.0 = wall.health.0.saturating_sub(wall.resisted_damage(damage));
+		let midpoint = GameCoord{x: (endpoints.0.x + endpoints.1.x)/2, y: (endpoints.0.y + endpoints.1.y)/2};
+		self.particles.emit(ParticleEffect::WallHit, midpoint);
+		if wall.health.0 == 0 {
+			self.particles.emit(ParticleEffect::WallDebris, midpoint);
+			self.remove_wall(endpoints);
+		}
+	}
+
+	pub fn damage_door(&mut self, endpoints: (GameCoord, GameCoord), damage: usize){
+		let Some(door) = self.doors.get_mut(endpoints) else { return };
+		door.health.0 = door.health.0.saturating_sub(damage);
+		let midpoint = GameCoord{x: (endpoints.0.x + endpoints.1.x)/2, y: (endpoints.0.y + endpoints.1.y)/2};
+		self.particles.emit(ParticleEffect::WallHit, midpoint);
+		if door.health.0 == 0 {
+			self.particles.emit(ParticleEffect::WallDebris, midpoint);
+			self.remove_door(endpoints);
+		}
+	}
+
+	pub fn damage_barrier(&mut self, endpoints: Edge, damage: usize){
+		//enemies don't care whether they're hitting a wall or a door
+		if self.walls.contains(endpoints) {
+			self.damage_wall(endpoints, damage);
+		} else {
+			self.damage_door(endpoints, damage);
+		}
+	}
+
+	pub fn damage_turret(&mut self, square: GridCoord, damage: u32){
+		let Some(turret) = self.turrets.get_mut(&square) else { return };
+		turret.health.0 = turret.health.0.saturating_sub(damage);
+		if turret.health.0 == 0 {
+			self.turrets.remove(&square);
+		}
+	}
+
+	pub fn damage_window(&mut self, endpoints: (GameCoord, GameCoord), damage: usize){
+		let Some(window) = self.windows.iter_mut().find(|window| window.endpoints == endpoints) else { return };
+		window.health.0 = window.health.0.saturating_sub(damage);
+		let midpoint = GameCoord{x: (endpoints.0.x + endpoints.1.x)/2, y: (endpoints.0.y + endpoints.1.y)/2};
+		self.particles.emit(if window.health.0 == 0 { ParticleEffect::WallDebris } else { ParticleEffect::WallHit }, midpoint);
+		self.windows.retain(|window| window.health.0 > 0);
+	}
+
+	fn find_projectile_hit(&self, projectile: &Projectile, segment: (GameCoord, GameCoord)) -> Option<ProjectileHit>{
+		let nearby_walls = self.walls.along_segment(segment.0, segment.1);
+		let nearby_doors = self.doors.along_segment(segment.0, segment.1);
+		let segment: ((i32, i32), (i32, i32)) = (segment.0.into(), segment.1.into());
+		let crosses_edge = |endpoints: &(GameCoord, GameCoord)| line_to_line_intersect(segment, (endpoints.0.into(), endpoints.1.into()));
+		if let Some(wall) = nearby_walls.into_iter().find(|wall| crosses_edge(&wall.endpoints)) {
+			return Some(ProjectileHit::Wall(wall.endpoints));
+		}
+		if let Some(window) = self.windows.iter().find(|window| crosses_edge(&window.endpoints)) {
+			return Some(ProjectileHit::Window(window.endpoints));
+		}
+		//friendly shots pass through doors, hostile ones hit them like walls
+		if let Faction::Hostile = projectile.faction {
+			if let Some(door) = nearby_doors.into_iter().find(|door| crosses_edge(&door.endpoints)) {
+				return Some(ProjectileHit::Door(door.endpoints));
+			}
+		}
+		if line_to_square_intersect(segment, (self.button.pos.center().into(), GridCoord::grid_size() as u32)) {
+			return Some(ProjectileHit::Button);
+		}
+		match projectile.faction {
+			Faction::Hostile => {
+				if line_to_square_intersect(segment, (self.player.game_coord.into(), BattlePlayerContext::width())) {
+					return Some(ProjectileHit::Player);
+				}
+				if let Some(turret) = self.turrets.values().find(|turret| line_to_square_intersect(segment, (turret.pos.center().into(), GridCoord::grid_size() as u32))) {
+					return Some(ProjectileHit::Turret(turret.pos));
+				}
+				self.other_players
+						.iter()
+						.position(|other| line_to_square_intersect(segment, (other.grid_coord.center().into(), BattlePlayerContext::width())))
+						.map(ProjectileHit::OtherPlayer)
+			},
+			Faction::Friendly => {
+				if let Some(enemy_index) = self.enemies.iter().position(|enemy| line_to_square_intersect(segment, (enemy.pos.into(), Enemy::width()))) {
+					return Some(ProjectileHit::Enemy(enemy_index));
+				}
+				self.bosses
+						.iter()
+						.position(|boss| line_to_square_intersect(segment, (boss.pos.into(), Boss::width())))
+						.map(ProjectileHit::Boss)
+			},
+		}
+	}
+
+	pub fn update_projectiles(&mut self){
+		let mut remaining_projectiles = Vec::new();
+		for mut projectile in std::mem::take(&mut self.projectiles){
+			let segment = projectile.advance();
+			let damage = projectile.damage.max(0) as u32;
+			match self.find_projectile_hit(&projectile, segment) {
+				Some(ProjectileHit::Wall(endpoints)) => self.damage_wall(endpoints, damage as usize),
+				Some(ProjectileHit::Window(endpoints)) => self.damage_window(endpoints, damage as usize),
+				Some(ProjectileHit::Door(endpoints)) => self.damage_door(endpoints, damage as usize),
+				Some(ProjectileHit::Button) => (),
+				Some(ProjectileHit::Turret(square)) => self.damage_turret(square, damage),
+				Some(ProjectileHit::Player) => {
+					self.player.health.0 = self.player.health.0.saturating_sub(damage as usize);
+					self.camera_state.add_trauma(damage as f32 / 40.0);
+				},
+				Some(ProjectileHit::OtherPlayer(index)) => {
+					self.other_players[index].health.0 = self.other_players[index].health.0.saturating_sub(damage);
+				},
+				Some(ProjectileHit::Enemy(index)) => {
+					self.enemies[index].health.0 = self.enemies[index].health.0.saturating_sub(damage);
+				},
+				Some(ProjectileHit::Boss(index)) => {
+					self.bosses[index].health.0 = self.bosses[index].health.0.saturating_sub(damage);
+				},
+				None if projectile.is_spent() => (),
+				None => remaining_projectiles.push(projectile),
+			}
+		}
+		self.projectiles = remaining_projectiles;
+	}
+
+	pub fn remove_wall(&mut self, endpoints: (GameCoord, GameCoord)) -> Option<Wall>{
+		let removed = self.walls.remove(endpoints)?;
+		self.wall_changes.push(WallChange::Destroyed(removed.endpoints));
+		Some(removed)
+	}
+
+	pub fn remove_door(&mut self, endpoints: (GameCoord, GameCoord)) -> Option<Door>{
+		let removed = self.doors.remove(endpoints)?;
+		self.wall_changes.push(WallChange::Destroyed(removed.endpoints));
+		Some(removed)
+	}
+
+	fn would_trap_players(&self, new_wall: Option<Edge>, new_blocked_square: Option<GridCoord>) -> bool{
+		//anyone who can get to the button now has to still be able to once this is built
+		//players open doors by walking into them, so they don't count here
+		let new_wall = new_wall.map(normalize_edge);
+		let can_step = |from: GridCoord, to: GridCoord, with_build: bool| -> bool {
+			let walled_off = self.walls.is_blocking(from, to) ||
+					(with_build && new_wall.is_some() && shared_edge(from, to).map(normalize_edge) == new_wall);
+			let blocked = self.turrets.contains_key(&to) || (with_build && new_blocked_square == Some(to));
+			self.terrain.get(to).is_passable() && !walled_off && !blocked
 		};
-		let to_return: HashSet<GridCoord> =  visible_squares
-			.into_iter()
-			.filter(|square|{
-				for wall in relevant_walls.clone() {
-					let wall_endpoints = ((wall.endpoints.0.x, wall.endpoints.0.y),(wall.endpoints.1.x, wall.endpoints.1.y));
-					let square_coords = (square.center().x, square.center().y);
-					if line_to_line_intersect((start_point, square_coords), wall_endpoints){
-						return false;
+		let player_squares = std::iter::once(self.player.game_coord.to_grid_coord())
+				.chain(self.other_players.iter().map(|other| other.grid_coord));
+		for square in player_squares{
+			let reachable_now = is_reachable(square, self.button.pos, |from, to| can_step(from, to, false));
+			if reachable_now && !is_reachable(square, self.button.pos, |from, to| can_step(from, to, true)){
+				return true;
+			}
+		}
+		false
+	}
+
+	fn check_cost(&self, cost: u32) -> Result<(), String>{
+		if self.player.materials < cost {
+			return Err(format!("Not enough materials, need {} but only have {}", cost, self.player.materials));
+		}
+		Ok(())
+	}
+
+	pub fn check_placement(&self, buildable: Buildable, site: BuildSite) -> Result<(), String>{
+		//everything that could stop a build, so the preview and the build itself always agree
+		match (buildable, site) {
+			(Buildable::Wall | Buildable::Door, BuildSite::Edge(endpoints)) => {
+				if self.terrain.blocks_building_on(endpoints){
+					return Err(String::from("Can't build on rubble"));
+				}
+				if let (Buildable::Wall, Some(wall)) = (buildable, self.walls.get(endpoints)){
+					if wall.material.upgrade().is_none(){
+						return Err(format!("Wall is already {:?}", wall.material));
 					}
+					return self.check_cost(buildable.cost(Some(wall)));
 				}
-				return true;
-			}).collect();
-		to_return
+				let occupied = self.walls.contains(endpoints) ||
+						self.doors.contains(endpoints) ||
+						self.windows.iter().any(|window| window.endpoints == endpoints || window.endpoints == (endpoints.1, endpoints.0));
+				if occupied{
+					return Err(String::from("Something is already built there"));
+				}
+				if let Buildable::Wall = buildable{
+					if self.would_trap_players(Some(endpoints), None){
+						return Err(String::from("That would wall someone in"));
+					}
+				}
+				self.check_cost(buildable.cost(None))
+			},
+			(Buildable::Deconstruct, BuildSite::Edge(endpoints)) => {
+				if !self.walls.contains(endpoints) && !self.doors.contains(endpoints){
+					return Err(String::from("Nothing there to take down"));
+				}
+				Ok(())
+			},
+			(Buildable::Trap(_) | Buildable::Turret, BuildSite::Square(square)) => {
+				let terrain = self.terrain.get(square);
+				if !terrain.is_passable() || terrain.blocks_building(){
+					return Err(format!("Can't build a {} on {:?}", buildable.get_hud_text(), terrain));
+				}
+				if square == self.button.pos || self.traps.contains_key(&square) || self.turrets.contains_key(&square){
+					return Err(String::from("Something is already built there"));
+				}
+				if let Buildable::Turret = buildable{
+					let someone_there = square == self.player.game_coord.to_grid_coord() ||
+							self.other_players.iter().any(|other| other.grid_coord == square);
+					if someone_there{
+						return Err(String::from("Someone is standing there"));
+					}
+					if self.would_trap_players(None, Some(square)){
+						return Err(String::from("That would wall someone in"));
+					}
+				}
+				self.check_cost(buildable.cost(None))
+			},
+			(buildable, site) => unreachable!("{:?} can't be built on {:?}", buildable, site),
+		}
 	}
 
-	pub fn get_learning_time(&self) -> u32{
-		30
+	pub fn place_buildable(&mut self, buildable: Buildable, site: BuildSite){
+		if let Err(reason) = self.check_placement(buildable, site){
+			println!("{}", reason);
+			self.build_feedback = Some((reason, 60));
+			return;
+		}
+		match site {
+			BuildSite::Edge(endpoints) if buildable == Buildable::Deconstruct => {
+				//give back part of what went into it
+				let refund = match self.remove_wall(endpoints) {
+					Some(wall) => wall.salvage_value(),
+					None => self.remove_door(endpoints).map_or(0, |_| Buildable::Door.cost(None) / 2),
+				};
+				self.player.materials += refund;
+				self.build_feedback = Some((format!("Deconstructed, got {} materials back", refund), 60));
+			},
+			BuildSite::Edge(endpoints) => {
+				let existing_wall = self.walls.get(endpoints).copied();
+				self.player.materials -= buildable.cost(existing_wall.as_ref());
+				match (buildable, existing_wall) {
+					(Buildable::Wall, Some(wall)) => {
+						//building over a wall we already have reinforces it instead
+						let material = wall.material.upgrade().unwrap();
+						self.walls.get_mut(endpoints).unwrap().upgrade_to(material);
+						self.build_feedback = Some((format!("Upgraded wall to {:?}", material), 60));
+						return;
+					},
+					(Buildable::Door, _) => {
+						self.doors.insert(Door::new(endpoints));
+					},
+					_ => {
+						self.walls.insert(Wall{ spent: WallMaterial::Wood.cost(), ..Wall::new(endpoints, WallMaterial::Wood) });
+					},
+				}
+				self.wall_changes.push(WallChange::Built(endpoints));
+			},
+			BuildSite::Square(square) => {
+				self.player.materials -= buildable.cost(None);
+				if let Buildable::Trap(kind) = buildable{
+					self.traps.insert(square, Trap::new(square, kind));
+				}else{
+					self.turrets.insert(square, Turret::new(square));
+				}
+			},
+		}
+	}
+
+	pub fn repair_at(&mut self, edge: Edge, square: GridCoord){
+		//turrets in the square we're facing take priority over the edge between us and them
+		if let Some(turret) = self.turrets.get_mut(&square){
+			turret.repair();
+		}else if let Some(wall) = self.walls.get_mut(edge){
+			wall.health.0 = (wall.health.0 + 20).min(wall.health.1);
+		}else if let Some(door) = self.doors.get_mut(edge){
+			door.health.0 = (door.health.0 + 20).min(door.health.1);
+		}
+	}
+
+	pub fn update_turrets(&mut self){
+		for turret in self.turrets.values_mut(){
+			if let Some(projectile) = turret.update(&self.enemies, &self.walls){
+				self.projectiles.push(projectile);
+			}
+		}
+	}
+
+	pub fn update_traps(&mut self, previous_squares: &Vec<GridCoord>){
+		//traps go off when an enemy steps into their square, not while it stands there
+		for trap in self.traps.values_mut(){
+			trap.update();
+		}
+		self.alarm = self.alarm.and_then(|(square, ticks_left)| if ticks_left > 0 { Some((square, ticks_left - 1)) } else { None });
+		for (enemy_index, previous_square) in previous_squares.iter().enumerate(){
+			let Some(enemy) = self.enemies.get_mut(enemy_index) else { break };
+			let square = enemy.pos.to_grid_coord();
+			if square == *previous_square{
+				continue;
+			}
+			let Some(trap) = self.traps.get_mut(&square) else { continue };
+			match trap.trigger(){
+				Some(TrapEffect::Damage(damage)) => enemy.health.0 = enemy.health.0.saturating_sub(damage),
+				Some(TrapEffect::Slow(ticks)) => enemy.slowed_ticks = ticks,
+				Some(TrapEffect::Alarm(square)) => {
+					self.alarm = Some((square, 90));
+				},
+				None => (),
+			}
+		}
+		self.traps.retain(|_, trap| !trap.is_broken());
+	}
+
+	pub fn update_doors(&mut self){
+		//doors swing open for anyone friendly walking into them and fall shut a moment after they leave
+		for door in self.doors.iter_mut(){
+			door.open_ticks = door.open_ticks.saturating_sub(1);
+		}
+		//reach a little past the body, so a player held back against a closed door still pushes it open
+		let player_body = (self.player.game_coord.into(), BattlePlayerContext::width() + 8);
+		let player_square = self.player.game_coord.to_grid_coord();
+		let other_player_squares = self.other_players.iter().map(|other| other.grid_coord).collect::<Vec<GridCoord>>();
+		for side in [Direction::North, Direction::South, Direction::East, Direction::West]{
+			let player_edge = player_square.edge(side);
+			if let Some(door) = self.doors.get_mut(player_edge){
+				if line_to_square_intersect((player_edge.0.into(), player_edge.1.into()), player_body){
+					door.open_ticks = Door::hold_open_time();
+				}
+			}
+			for other_square in &other_player_squares{
+				//we only know which square other players are in, so being next to a door is enough
+				if let Some(door) = self.doors.get_mut(other_square.edge(side)){
+					door.open_ticks = Door::hold_open_time();
+				}
+			}
+		}
 	}
 
 	pub fn handle_tick(game_obj: &mut GameObject, input_state: &InputState, my_sound_manager: &mut SoundManager){
 		let GameContext::Battle(battle_context) = &mut game_obj.phase else {unreachable!("Game object is not in Battle phase")};
 		battle_context.round_time += 1;
+		battle_context.camera_state.viewport = game_obj.viewport;
 		let learning_timer = battle_context.get_learning_time();
 		let battle_player = &mut battle_context.player;
 
 		//check collisions
-		let player_grid_corner_coords = [
-			battle_player.game_coord.to_grid_coord().top_left(),
-			battle_player.game_coord.to_grid_coord().top_right(),
-			battle_player.game_coord.to_grid_coord().bottom_right(),
-			battle_player.game_coord.to_grid_coord().bottom_left()
-		];
-		//find wall above player if one exists
-		let top_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-			let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-			return (player_grid_corner_coords[0] == wall_corners.0 && player_grid_corner_coords[1] == wall_corners.1) || (player_grid_corner_coords[0] == wall_corners.1 && player_grid_corner_coords[1] == wall_corners.0);
-		});
-		let right_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-			let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-			return (player_grid_corner_coords[1] == wall_corners.0 && player_grid_corner_coords[2] == wall_corners.1) || (player_grid_corner_coords[1] == wall_corners.1 && player_grid_corner_coords[2] == wall_corners.0);
-		});
-		let bottom_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-			let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-			return (player_grid_corner_coords[2] == wall_corners.0 && player_grid_corner_coords[3] == wall_corners.1) || (player_grid_corner_coords[2] == wall_corners.1 && player_grid_corner_coords[3] == wall_corners.0);
-		});
-		let left_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-			let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-			return (player_grid_corner_coords[3] == wall_corners.0 && player_grid_corner_coords[0] == wall_corners.1) || (player_grid_corner_coords[3] == wall_corners.1 && player_grid_corner_coords[0] == wall_corners.0);
-		});
+		let player_square = battle_player.game_coord.to_grid_coord();
+		//closed doors stop players too, until walking into them swings them open
+		let player_barrier_on_side = |side: Direction| -> Option<Edge> {
+			battle_context.walls.on_side(player_square, side).map(|wall| wall.endpoints)
+					.or_else(|| battle_context.doors.on_side_for(player_square, side, Faction::Friendly).map(|door| door.endpoints))
+		};
+		let top_wall = player_barrier_on_side(Direction::North);
+		let right_wall = player_barrier_on_side(Direction::East);
+		let bottom_wall = player_barrier_on_side(Direction::South);
+		let left_wall = player_barrier_on_side(Direction::West);
 		let player_collisions = battle_player.get_collisions(top_wall, right_wall, bottom_wall, left_wall);
 		if player_collisions.0{
 			//snap player to bottom of top wall
-			battle_player.game_coord.y = top_wall.unwrap().endpoints.0.y + GridCoord::grid_size()/2;
+			battle_player.game_coord.y = top_wall.unwrap().0.y + GridCoord::grid_size()/2;
 		}
 		if player_collisions.1{
 			//snap player to left of right wall
-			battle_player.game_coord.x = right_wall.unwrap().endpoints.0.x - GridCoord::grid_size()/2;
+			battle_player.game_coord.x = right_wall.unwrap().0.x - GridCoord::grid_size()/2;
 		}
 		if player_collisions.2{
 			//snap player to top of bottom wall
-			battle_player.game_coord.y = bottom_wall.unwrap().endpoints.0.y - GridCoord::grid_size()/2;
+			battle_player.game_coord.y = bottom_wall.unwrap().0.y - GridCoord::grid_size()/2;
 		}
 		if player_collisions.3{
 			//snap player to right of left wall
-			battle_player.game_coord.x = left_wall.unwrap().endpoints.0.x + GridCoord::grid_size()/2;
+			battle_player.game_coord.x = left_wall.unwrap().0.x + GridCoord::grid_size()/2;
 		}
 
 		//check collisions for all enemies
 		for enemy in &mut battle_context.enemies{
-			let enemy_grid_corner_coords = [
-				enemy.pos.to_grid_coord().top_left(),
-				enemy.pos.to_grid_coord().top_right(),
-				enemy.pos.to_grid_coord().bottom_right(),
-				enemy.pos.to_grid_coord().bottom_left()
-			];
-			let top_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-				let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-				return (enemy_grid_corner_coords[0] == wall_corners.0 && enemy_grid_corner_coords[1] == wall_corners.1) || (enemy_grid_corner_coords[0] == wall_corners.1 && enemy_grid_corner_coords[1] == wall_corners.0);
-			});
-			let right_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-				let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-				return (enemy_grid_corner_coords[1] == wall_corners.0 && enemy_grid_corner_coords[2] == wall_corners.1) || (enemy_grid_corner_coords[1] == wall_corners.1 && enemy_grid_corner_coords[2] == wall_corners.0);
-			});
-			let bottom_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-				let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-				return (enemy_grid_corner_coords[2] == wall_corners.0 && enemy_grid_corner_coords[3] == wall_corners.1) || (enemy_grid_corner_coords[2] == wall_corners.1 && enemy_grid_corner_coords[3] == wall_corners.0);
-			});
-			let left_wall: Option<&Wall> = battle_context.walls.iter().find(|wall| {
-				let wall_corners = (wall.endpoints.0, wall.endpoints.1);
-				return (enemy_grid_corner_coords[3] == wall_corners.0 && enemy_grid_corner_coords[0] == wall_corners.1) || (enemy_grid_corner_coords[3] == wall_corners.1 && enemy_grid_corner_coords[0] == wall_corners.0);
-			});
+			//doors are as solid as walls to enemies
+			let enemy_square = enemy.pos.to_grid_coord();
+			let barrier_on_side = |side: Direction| -> Option<Edge> {
+				battle_context.walls.on_side(enemy_square, side).map(|wall| wall.endpoints)
+						.or_else(|| battle_context.doors.on_side_for(enemy_square, side, Faction::Hostile).map(|door| door.endpoints))
+			};
+			let top_wall = barrier_on_side(Direction::North);
+			let right_wall = barrier_on_side(Direction::East);
+			let bottom_wall = barrier_on_side(Direction::South);
+			let left_wall = barrier_on_side(Direction::West);
 			let enemy_collisions = enemy.get_collisions(top_wall, right_wall, bottom_wall, left_wall);
 			if enemy_collisions.0{
 				//snap enemy to bottom of top wall
-				enemy.pos.y = top_wall.unwrap().endpoints.0.y + GridCoord::grid_size()/2;
+				enemy.pos.y = top_wall.unwrap().0.y + GridCoord::grid_size()/2;
 			}
 			if enemy_collisions.1{
 				//snap enemy to left of right wall
-				enemy.pos.x = right_wall.unwrap().endpoints.0.x - GridCoord::grid_size()/2;
+				enemy.pos.x = right_wall.unwrap().0.x - GridCoord::grid_size()/2;
 			}
 			if enemy_collisions.2{
 				//snap enemy to top of bottom wall
-				enemy.pos.y = bottom_wall.unwrap().endpoints.0.y - GridCoord::grid_size()/2;
+				enemy.pos.y = bottom_wall.unwrap().0.y - GridCoord::grid_size()/2;
 			}
 			if enemy_collisions.3{
 				//snap enemy to right of left wall
-				enemy.pos.x = left_wall.unwrap().endpoints.0.x + GridCoord::grid_size()/2;
+				enemy.pos.x = left_wall.unwrap().0.x + GridCoord::grid_size()/2;
 			}
 		}
 
@@ -268,16 +775,32 @@ impl BattleContext{
 			BattleState::Live => {
 				//TODO check for received moves
 				//TODO update world
-				battle_context.camera_state.smooth_scroll(&battle_player.game_coord);
+				let camera = &mut battle_context.camera_state;
+				camera.adjust_zoom(-(input_state.right_y_pos as f32) / i16::MAX as f32);
+				camera.update_mode_button(input_state.right_stick);
+				battle_context.minimap.update_toggle(input_state.btn_back);
+				match camera.mode {
+					CameraMode::FollowPlayer => camera.smooth_scroll(&battle_player.game_coord),
+					CameraMode::FrameAllPlayers => {
+						let player_positions = std::iter::once(battle_player.game_coord)
+								.chain(battle_context.other_players.iter().map(|other| other.grid_coord.center()))
+								.collect();
+						camera.frame_players(&player_positions);
+					}
+				}
 				battle_context.button.update();
+				//cycle what the build ability makes each time the select button is released
+				if input_state.left_shoulder && battle_player.has_ability(Build) && matches!(battle_player.state, PlayerState::Standing | PlayerState::Running){
+					battle_player.state = PlayerState::BuildChoosing;
+				}
+				let mut pending_build: Option<(Buildable, BuildSite)> = None;
+				let mut pending_repair: Option<(Edge, GridCoord)> = None;
 				match (&battle_player.state, get_player_intent_vector(input_state), &input_state.btn_down, &input_state.btn_right){
 					(PlayerState::Standing, None, false, false) => (),
 					(PlayerState::Standing, Some(x), false, false) => {
 						battle_player.facing_vector = x;
 						battle_player.snapped_facing_vector = Direction::from_facing_vector(x);
-						const RUNNING_SPEED: f32 = BattlePlayerContext::running_speed();
-						battle_player.game_coord.y -= (battle_player.facing_vector.sin() * RUNNING_SPEED) as i32;
-						battle_player.game_coord.x += (battle_player.facing_vector.cos() * RUNNING_SPEED) as i32;
+						battle_player.run(&battle_context.terrain);
 						battle_player.state = PlayerState::Running;
 					},
 					(PlayerState::Standing,_, true, false) =>{
@@ -294,7 +817,7 @@ impl BattleContext{
 								Armor => PlayerState::Standing,
 								RangeAttack => PlayerState::RangeTargeting,
 								Vision => PlayerState::Standing,
-								Build => PlayerState::BuildPlacing(0, 25),
+								Build => PlayerState::BuildPlacing(0, battle_player.build_time(&battle_context.walls)),
 								Repair => PlayerState::Repairing(0, 25),
 								ButtonPress => PlayerState::ButtonPressing(0, 25),
 								Heal => PlayerState::Healing(0, 25)
@@ -315,7 +838,7 @@ impl BattleContext{
 								Armor => PlayerState::Standing,
 								RangeAttack => PlayerState::RangeTargeting,
 								Vision => PlayerState::Standing,
-								Build => PlayerState::BuildPlacing(0, 25),
+								Build => PlayerState::BuildPlacing(0, battle_player.build_time(&battle_context.walls)),
 								Repair => PlayerState::Repairing(0, 25),
 								ButtonPress => PlayerState::ButtonPressing(0, 25),
 								Heal => PlayerState::Healing(0, 25)
@@ -330,9 +853,7 @@ impl BattleContext{
 						//still running
 						battle_player.facing_vector = x;
 						battle_player.snapped_facing_vector = Direction::from_facing_vector(x);
-						const RUNNING_SPEED: f32 = BattlePlayerContext::running_speed();
-						battle_player.game_coord.x += (battle_player.facing_vector.cos() * RUNNING_SPEED) as i32;
-						battle_player.game_coord.y -= (battle_player.facing_vector.sin() * RUNNING_SPEED) as i32;
+						battle_player.run(&battle_context.terrain);
 					},
 					(PlayerState::Running, _, true, false) =>{
 						battle_player.state = PlayerState::Learning(ActionButton::Primary, 0, learning_timer)
@@ -411,16 +932,7 @@ impl BattleContext{
 					},
 					(PlayerState::BuildPlacing(curr, max), _, true, false) if curr >= max => {
 						if let Build = battle_player.ability_primary {
-							let build_endpoints = match battle_player.snapped_facing_vector{
-								Direction::North => (battle_player.game_coord.to_grid_coord().top_left(), battle_player.game_coord.to_grid_coord().top_right()),
-								Direction::South => (battle_player.game_coord.to_grid_coord().bottom_left(), battle_player.game_coord.to_grid_coord().bottom_right()),
-								Direction::West => (battle_player.game_coord.to_grid_coord().top_left(), battle_player.game_coord.to_grid_coord().bottom_left()),
-								Direction::East => (battle_player.game_coord.to_grid_coord().top_right(), battle_player.game_coord.to_grid_coord().bottom_right())
-							};
-							let new_wall = Wall { endpoints: build_endpoints, health: (100, 100) };
-							if battle_context.walls.iter().all(|wall| wall.endpoints != new_wall.endpoints){
-								battle_context.walls.push(new_wall);
-							}
+							pending_build = Some((battle_player.build_selection, battle_player.build_site()));
 						}
 						battle_player.state = PlayerState::Standing;
 					},
@@ -436,16 +948,7 @@ impl BattleContext{
 					},
 					(PlayerState::BuildPlacing(curr, max), _, false, true) if curr >= max => {
 						if let Build = battle_player.ability_secondary {
-							let build_endpoints = match battle_player.snapped_facing_vector{
-								Direction::North => (battle_player.game_coord.to_grid_coord().top_left(), battle_player.game_coord.to_grid_coord().top_right()),
-								Direction::South => (battle_player.game_coord.to_grid_coord().bottom_left(), battle_player.game_coord.to_grid_coord().bottom_right()),
-								Direction::West => (battle_player.game_coord.to_grid_coord().top_left(), battle_player.game_coord.to_grid_coord().bottom_left()),
-								Direction::East => (battle_player.game_coord.to_grid_coord().top_right(), battle_player.game_coord.to_grid_coord().bottom_right())
-							};
-							let new_wall = Wall { endpoints: build_endpoints, health: (100, 100) };
-							if battle_context.walls.iter().all(|wall| wall.endpoints != new_wall.endpoints){
-								battle_context.walls.push(new_wall);
-							}
+							pending_build = Some((battle_player.build_selection, battle_player.build_site()));
 						}
 						battle_player.state = PlayerState::Standing;
 					},
@@ -453,64 +956,171 @@ impl BattleContext{
 						println!("Player state: {:?}, direction: {:?}, primary: {}, secondary: {}", battle_player.state, facing, primary, secondary);
 						todo!("Button combo for build placing not implemented")
 					},
+					(PlayerState::BuildChoosing, _, _, _) => {
+						if !input_state.left_shoulder{
+							battle_player.build_selection = battle_player.build_selection.next();
+							println!("Building {}", battle_player.build_selection.get_hud_text());
+							battle_player.state = PlayerState::Standing;
+						}
+					},
 					(PlayerState::MeleeAttacking(_,_), _, _, _) => {
 						battle_player.state = PlayerState::Standing;
 						//TODO implement
 					},
 					(PlayerState::RangeTargeting, _, _, _) =>{
+						//fire straight along the facing vector, then wait out the reload
+						let range = Projectile::default_range();
+						let target = GameCoord{
+							x: battle_player.game_coord.x + (battle_player.facing_vector.cos() * range) as i32,
+							y: battle_player.game_coord.y - (battle_player.facing_vector.sin() * range) as i32,
+						};
+						battle_context.projectiles.push(Projectile::new(Faction::Friendly, battle_player.game_coord, target, 25));
+						battle_player.state = PlayerState::RangeAttacking(0, 15);
+					},
+					(PlayerState::RangeAttacking(curr, max), _, _, _) if curr < max =>{
+						battle_player.state = PlayerState::RangeAttacking(curr+1, *max);
+					},
+					(PlayerState::RangeAttacking(_, _), _, _, _) =>{
 						battle_player.state = PlayerState::Standing;
-						//TODO implement
 					},
 					(PlayerState::Healing(_,_), _, _, _) =>{
 						battle_player.state = PlayerState::Standing;
 						//TODO implement
 					},
-					(PlayerState::Repairing(_,_), _, _, _) =>{
+					(PlayerState::Repairing(_,_), _, false, false) =>{
 						battle_player.state = PlayerState::Standing;
-						//TODO implement
+					},
+					(PlayerState::Repairing(curr, max), facing, primary, secondary) =>{
+						//keep patching up whatever is in front of us for as long as the button is held
+						let holding_repair = (*primary && battle_player.ability_primary == Repair) || (*secondary && battle_player.ability_secondary == Repair);
+						if let Some(x) = facing {
+							battle_player.snapped_facing_vector = Direction::from_facing_vector(x);
+							battle_player.facing_vector = x;
+						}
+						battle_player.state = if !holding_repair {
+							PlayerState::Standing
+						}else if curr < max {
+							PlayerState::Repairing(curr+1, *max)
+						}else{
+							pending_repair = Some((battle_player.facing_edge(), battle_player.facing_square()));
+							PlayerState::Repairing(0, *max)
+						};
 					},
 					(PlayerState::ButtonPressing(_, _), _, _, _) =>{
 						battle_player.state = PlayerState::Standing;
 						//TODO implement
 					},
-					(s,d,a,b)=>{
-						println!("Not Implemented: Player state: {:?}, direction: {:?}, a: {}, b: {}", s, d, a, b);
-						todo!("Not implemented")
-					},
+				}
+				battle_context.build_feedback = battle_context.build_feedback
+						.take()
+						.and_then(|(reason, ticks_left)| if ticks_left > 0 { Some((reason, ticks_left - 1)) } else { None });
+				if let Some((buildable, site)) = pending_build{
+					battle_context.place_buildable(buildable, site);
+				}
+				if let Some((edge, square)) = pending_repair{
+					battle_context.repair_at(edge, square);
+				}
+				battle_context.update_doors();
+				battle_context.build_preview = match battle_context.player.state {
+					PlayerState::BuildPlacing(_, _) => Some(battle_context.check_placement(battle_context.player.build_selection, battle_context.player.build_site())),
+					_ => None,
+				};
+				//replan any cached enemy paths affected by walls built or destroyed since last tick
+				if !battle_context.wall_changes.is_empty(){
+					let enemy_coords = battle_context.enemies.iter().map(|enemy| enemy.pos.to_grid_coord()).collect::<Vec<GridCoord>>();
+					for enemy in &mut battle_context.enemies{
+						enemy.handle_wall_changes(&battle_context.wall_changes, &battle_context.walls, &battle_context.doors, &enemy_coords, &battle_context.terrain);
+					}
+					battle_context.wall_changes.clear();
 				}
 				//TODO handle enemy moves
 				let all_enemies = battle_context.enemies.clone();
-				for enemy in &mut battle_context.enemies{
+				let perceived_player = battle_context.get_enemy_perception();
+				let mut barrier_strikes: Vec<(Edge, usize)> = Vec::new();
+				let mut turret_strikes: Vec<(GridCoord, u32)> = Vec::new();
+				for (enemy_index, enemy) in battle_context.enemies.iter_mut().enumerate(){
+					let neighbors = all_enemies.iter()
+							.enumerate()
+							.filter(|(other_index, _)| *other_index != enemy_index)
+							.map(|(_, other)| other.pos)
+							.collect::<Vec<GameCoord>>();
+					enemy.attack_cooldown = enemy.attack_cooldown.saturating_sub(1);
+					enemy.slowed_ticks = enemy.slowed_ticks.saturating_sub(1);
 					match &enemy.behavior{
 						EnemyBehavior::Idle => {
-							//if player is within 5 squares of enemy, switch to attacking
-							let player_square = battle_player.clone().game_coord.to_grid_coord();
+							//if the enemy can see or hear the player, or was alerted by another enemy, switch to attacking
+							let player_square = battle_context.player.game_coord.to_grid_coord();
 							let enemy_square = enemy.pos.to_grid_coord();
-							let player_distance = player_square.pythagorean_distance_to(&enemy_square);
 							let walls = &battle_context.walls;
-							let enemy_coords =  all_enemies.iter().map(|enemy| enemy.pos.to_grid_coord()).collect::<Vec<GridCoord>>();
-							if player_distance <= 7.0{
-								if let Some(path_to_player) = path_to(enemy_square, player_square, walls, &enemy_coords){
+							let enemy_coords =  all_enemies.iter()
+									.map(|enemy| enemy.pos.to_grid_coord())
+									.chain(battle_context.bosses.iter().flat_map(|boss| boss.footprint_squares()))
+									.chain(battle_context.turrets.keys().copied())
+									.collect::<Vec<GridCoord>>();
+							//turrets the enemy can see get dealt with before anything else
+							let spotted_turret = battle_context.turrets.values()
+									.filter(|turret| enemy_square.pythagorean_distance_to(&turret.pos) <= Enemy::vision_range() as f32)
+									.filter(|turret| has_line_of_sight(enemy.pos, turret.pos.center(), walls, None))
+									.min_by(|turret1, turret2| {
+										let distance1 = turret1.pos.pythagorean_distance_to(&enemy_square);
+										let distance2 = turret2.pos.pythagorean_distance_to(&enemy_square);
+										distance1.partial_cmp(&distance2).unwrap()
+									})
+									.map(|turret| turret.pos);
+							let path_to_turret = spotted_turret.and_then(|turret_square| {
+								//path into the turret's square, then stop one short of it
+								let other_coords = enemy_coords.iter().copied().filter(|square| *square != turret_square).collect::<Vec<GridCoord>>();
+								let mut path = path_to(enemy_square, turret_square, walls, &battle_context.doors, &other_coords, &battle_context.terrain)?;
+								path.pop();
+								Some(path)
+							});
+							if perceived_player[enemy_index]{
+								if enemy.in_ranged_attack_position(battle_context.player.game_coord, walls, None){
+									enemy.behavior = EnemyBehavior::Skirmishing(0, 90);
+								}else if let Some(path_to_player) = path_to(enemy_square, player_square, walls, &battle_context.doors, &enemy_coords, &battle_context.terrain){
 									enemy.behavior = EnemyBehavior::TargetPlayer(0, 150, path_to_player);
 								}
+							}else if let Some(turret_square) = spotted_turret.filter(|turret_square| enemy.in_ranged_attack_position(turret_square.center(), walls, None)){
+								enemy.behavior = EnemyBehavior::ShootWalls(0, 150, turret_square.center());
+							}else if let Some(path) = path_to_turret{
+								enemy.behavior = EnemyBehavior::AttackWalls(0, 150, path);
 							}else{
-								if let Some(path_to_button) = path_to(enemy_square, battle_context.button.pos, walls, &enemy_coords) {
+								if let Some(path_to_button) = path_to(enemy_square, battle_context.button.pos, walls, &battle_context.doors, &enemy_coords, &battle_context.terrain) {
 									enemy.behavior = EnemyBehavior::WalkToButton(0, 150, path_to_button);
 								}else{
-									//find closest wall to enemy
-									let closest_wall = walls.iter().min_by(|wall1, wall2| {
-										let wall1_distance = wall1.endpoints.0.pythagorean_distance_to(&enemy.pos) + wall1.endpoints.1.pythagorean_distance_to(&enemy.pos);
-										let wall2_distance = wall2.endpoints.0.pythagorean_distance_to(&enemy.pos) + wall2.endpoints.1.pythagorean_distance_to(&enemy.pos);
-										wall1_distance.partial_cmp(&wall2_distance).unwrap()
-									});
+									//find closest wall or door to enemy
+									//look close by first, only falling back to every barrier on the map if there's nothing nearby
+									let mut candidate_barriers = walls.near(enemy_square, Enemy::barrier_search_reach())
+											.into_iter()
+											.map(|wall| wall.endpoints)
+											.chain(battle_context.doors.near(enemy_square, Enemy::barrier_search_reach()).into_iter().map(|door| door.endpoints))
+											.collect::<Vec<Edge>>();
+									if candidate_barriers.is_empty(){
+										candidate_barriers = walls.iter()
+												.map(|wall| wall.endpoints)
+												.chain(battle_context.doors.iter().map(|door| door.endpoints))
+												.collect();
+									}
+									let closest_wall = candidate_barriers
+											.into_iter()
+											.min_by(|wall1, wall2| {
+												let wall1_distance = wall1.0.pythagorean_distance_to(&enemy.pos) + wall1.1.pythagorean_distance_to(&enemy.pos);
+												let wall2_distance = wall2.0.pythagorean_distance_to(&enemy.pos) + wall2.1.pythagorean_distance_to(&enemy.pos);
+												wall1_distance.partial_cmp(&wall2_distance).unwrap()
+											});
 									if let Some(wall) = closest_wall{
 										//there should be 2 squares bordering the wall, find the one that's closest to the enemy
 										//get the midpoint between the 2 game coords
 										//get the slope between the 2 game coords
 										//add and subtract half the grid width along the reciprocal of the slope to the midpoint
 										//pick the one that's closest to the enemy
-										let midpoint = GameCoord{x: (wall.endpoints.0.x + wall.endpoints.1.x)/2, y: (wall.endpoints.0.y + wall.endpoints.1.y)/2};
-										let (dx, dy) = (wall.endpoints.1.x - wall.endpoints.0.x, wall.endpoints.1.y - wall.endpoints.0.y);
+										let midpoint = GameCoord{x: (wall.0.x + wall.1.x)/2, y: (wall.0.y + wall.1.y)/2};
+										//ranged enemies don't need to walk up to the wall if they can already shoot it
+										if enemy.in_ranged_attack_position(midpoint, walls, Some(wall)){
+											enemy.behavior = EnemyBehavior::ShootWalls(0, 150, midpoint);
+											continue;
+										}
+										let (dx, dy) = (wall.1.x - wall.0.x, wall.1.y - wall.0.y);
 										let width = GridCoord::grid_size();
 										let target_points = match (dx,dy){
 											(0,_) => (GameCoord{x: midpoint.x - width/2 , y: midpoint.y}, GameCoord{x: midpoint.x + width/2, y: midpoint.y}),
@@ -526,7 +1136,7 @@ impl BattleContext{
 												}).unwrap_or(&target_points.0).to_grid_coord();
 										//println!("Target grid square: {:?}", target_grid_square);
 
-										let path_to_wall = path_to(enemy_square,target_grid_square, walls, &enemy_coords);
+										let path_to_wall = path_to(enemy_square,target_grid_square, walls, &battle_context.doors, &enemy_coords, &battle_context.terrain);
 										if let Some(path) = path_to_wall {
 											println!("We're attacking a wall");
 											enemy.behavior = EnemyBehavior::AttackWalls(0, 150, path)
@@ -554,13 +1164,12 @@ impl BattleContext{
 								if enemy.pos.to_grid_coord() == *next_square {
 									enemy.behavior = EnemyBehavior::WalkToButton(curr + 1, *max, path[1..].to_vec());
 								}else{
-									//move towards the next square
-									let target = next_square.center();
-									let enemy_pos = enemy.pos;
-									let angle = (target.y as f32 - enemy_pos.y as f32).atan2(target.x as f32 - enemy_pos.x as f32);
-
-									enemy.pos.x += (angle.cos() * Enemy::speed()) as i32;
-									enemy.pos.y += (angle.sin() * Enemy::speed()) as i32;
+									//move towards the next square, steering away from any crowded neighbors
+									let next_pos = enemy.steered_step_towards(next_square.center(), &neighbors, &battle_context.terrain);
+									if let Some(direction) = Direction::from_movement(enemy.pos, next_pos){
+										enemy.snapped_facing_vector = direction;
+									}
+									enemy.pos = next_pos;
 									enemy.behavior = EnemyBehavior::WalkToButton(curr + 1, *max, path.to_vec());
 								}
 							}else{
@@ -576,17 +1185,20 @@ impl BattleContext{
 							if *curr == 0{
 								println!("Started targeting player");
 							}
-							if let Some(next_square) = path.first(){
+							if perceived_player[enemy_index] && enemy.in_ranged_attack_position(battle_context.player.game_coord, &battle_context.walls, None){
+								//close enough to shoot, stop walking
+								enemy.behavior = EnemyBehavior::Skirmishing(0, 90);
+							}else if let Some(next_square) = path.first(){
 								if enemy.pos.to_grid_coord() == *next_square {
-									enemy.behavior = EnemyBehavior::WalkToButton(curr + 1, *max, path[1..].to_vec());
+									enemy.behavior = EnemyBehavior::TargetPlayer(curr + 1, *max, path[1..].to_vec());
 								}else{
-									//move towards the next square
-									let target = next_square.center();
-									let enemy_pos = enemy.pos;
-									let angle = (target.y as f32 - enemy_pos.y as f32).atan2(target.x as f32 - enemy_pos.x as f32);
-									enemy.pos.x += (angle.cos() * Enemy::speed()) as i32;
-									enemy.pos.y += (angle.sin() * Enemy::speed()) as i32;
-									enemy.behavior = EnemyBehavior::WalkToButton(curr + 1, *max, path.to_vec());
+									//move towards the next square, steering away from any crowded neighbors
+									let next_pos = enemy.steered_step_towards(next_square.center(), &neighbors, &battle_context.terrain);
+									if let Some(direction) = Direction::from_movement(enemy.pos, next_pos){
+										enemy.snapped_facing_vector = direction;
+									}
+									enemy.pos = next_pos;
+									enemy.behavior = EnemyBehavior::TargetPlayer(curr + 1, *max, path.to_vec());
 								}
 							}else{
 								//println!("Path exhausted");
@@ -606,15 +1218,27 @@ impl BattleContext{
 								if enemy.pos.to_grid_coord() == *next_square {
 									enemy.behavior = EnemyBehavior::AttackWalls(curr + 1, *max, path[1..].to_vec());
 								}else{
-									//move towards the next square
-									let target = next_square.center();
-									let enemy_pos = enemy.pos;
-									let angle = (target.y as f32 - enemy_pos.y as f32).atan2(target.x as f32 - enemy_pos.x as f32);
-
-									enemy.pos.x += (angle.cos() * Enemy::speed()) as i32;
-									enemy.pos.y += (angle.sin() * Enemy::speed()) as i32;
+									//move towards the next square, steering away from any crowded neighbors
+									let next_pos = enemy.steered_step_towards(next_square.center(), &neighbors, &battle_context.terrain);
+									if let Some(direction) = Direction::from_movement(enemy.pos, next_pos){
+										enemy.snapped_facing_vector = direction;
+									}
+									enemy.pos = next_pos;
 									enemy.behavior = EnemyBehavior::AttackWalls(curr + 1, *max, path.to_vec());
 								}
+							}else if let Some(turret_square) = enemy.adjacent_turret(&battle_context.turrets, &battle_context.walls, &battle_context.doors){
+								let (curr, max) = (*curr, *max);
+								if enemy.try_strike(){
+									turret_strikes.push((turret_square, Enemy::attack_power()));
+								}
+								enemy.behavior = EnemyBehavior::AttackWalls(curr + 1, max, Vec::new());
+							}else if let Some(barrier) = enemy.adjacent_barrier(&battle_context.walls, &battle_context.doors){
+								//arrived, keep hitting whatever is in the way until it breaks or we run out of time
+								let (curr, max) = (*curr, *max);
+								if enemy.try_strike(){
+									barrier_strikes.push((barrier, Enemy::attack_power() as usize));
+								}
+								enemy.behavior = EnemyBehavior::AttackWalls(curr + 1, max, Vec::new());
 							}else{
 								//path exhausted
 								//enemy.behavior = EnemyBehavior::Idle;
@@ -622,6 +1246,37 @@ impl BattleContext{
 								enemy.behavior = EnemyBehavior::WalkToButton(curr+1, *max, path.to_vec());
 							}
 						}
+						EnemyBehavior::Skirmishing(curr, max) if curr >= max => {
+							enemy.behavior = EnemyBehavior::Idle;
+						},
+						EnemyBehavior::Skirmishing(curr, max) => {
+							let (curr, max) = (*curr, *max);
+							let player_pos = battle_context.player.game_coord;
+							if !perceived_player[enemy_index] || !enemy.in_ranged_attack_position(player_pos, &battle_context.walls, None){
+								enemy.behavior = EnemyBehavior::Idle;
+							}else{
+								let player_distance = enemy.pos.to_grid_coord().pythagorean_distance_to(&player_pos.to_grid_coord());
+								if player_distance < Enemy::ranged_retreat_range(){
+									//back straight away from the player to keep our distance
+									let retreat_target = GameCoord{x: 2*enemy.pos.x - player_pos.x, y: 2*enemy.pos.y - player_pos.y};
+									enemy.pos = enemy.steered_step_towards(retreat_target, &neighbors, &battle_context.terrain);
+								}
+								if let Some(projectile) = enemy.try_fire_at(player_pos){
+									battle_context.projectiles.push(projectile);
+								}
+								enemy.behavior = EnemyBehavior::Skirmishing(curr + 1, max);
+							}
+						},
+						EnemyBehavior::ShootWalls(curr, max, _) if curr >= max => {
+							enemy.behavior = EnemyBehavior::Idle;
+						},
+						EnemyBehavior::ShootWalls(curr, max, target) => {
+							let (curr, max, target) = (*curr, *max, *target);
+							if let Some(projectile) = enemy.try_fire_at(target){
+								battle_context.projectiles.push(projectile);
+							}
+							enemy.behavior = EnemyBehavior::ShootWalls(curr + 1, max, target);
+						},
 						EnemyBehavior::WalkToButton(_, _, _) => {
 							todo!("Implement WalkToButton")
 						}
@@ -633,6 +1288,48 @@ impl BattleContext{
 						}
 					}
 				}
+				if battle_context.round_time == battle_context.get_boss_arrival_time(){
+					let spawn_point = battle_context.get_boss_spawn_point();
+					battle_context.bosses.push(Boss::new(spawn_point));
+				}
+				let mut boss_actions = Vec::new();
+				for boss in &mut battle_context.bosses{
+					boss_actions.extend(boss.update(battle_context.player.game_coord, battle_context.button.pos, &battle_context.walls, &battle_context.doors, &battle_context.terrain));
+				}
+				let previous_enemy_squares = all_enemies.iter().map(|enemy| enemy.pos.to_grid_coord()).collect::<Vec<GridCoord>>();
+				battle_context.update_traps(&previous_enemy_squares);
+				for (barrier, damage) in barrier_strikes{
+					battle_context.damage_barrier(barrier, damage);
+				}
+				for (turret_square, damage) in turret_strikes{
+					battle_context.damage_turret(turret_square, damage);
+				}
+				battle_context.update_turrets();
+				for action in boss_actions{
+					battle_context.apply_boss_action(action);
+				}
+				battle_context.update_projectiles();
+				for enemy in &mut battle_context.enemies{
+					//anything shoved into a pit is gone for good
+					if let Terrain::Pit = battle_context.terrain.get(enemy.pos.to_grid_coord()){
+						enemy.health.0 = 0;
+					}
+				}
+				//every kill leaves behind something worth salvaging
+				let kills = battle_context.enemies.iter().filter(|enemy| enemy.health.0 == 0).count();
+				for enemy in battle_context.enemies.iter().filter(|enemy| enemy.health.0 == 0){
+					battle_context.particles.emit(ParticleEffect::EnemyDeath, enemy.pos);
+				}
+				battle_context.player.materials += kills as u32;
+				battle_context.enemies.retain(|enemy| enemy.health.0 > 0);
+				battle_context.bosses.retain(|boss| boss.health.0 > 0);
+				if battle_context.player.health.0 == 0{
+					battle_context.state = BattleState::Finished;
+				}
+				battle_context.resolve_body_collisions();
+				battle_context.update_fog();
+				battle_context.update_animations();
+				battle_context.update_particles();
 				//TODO broadcast moves
 			},
 			BattleState::Finished => (),
@@ -646,7 +1343,7 @@ pub enum ActionButton {
 	Secondary
 }
 
-pub fn draw_grid(canvas: &mut WindowCanvas, _background_texture: &Texture, ctx: &BattleContext){
+pub fn draw_grid<T: RenderTarget>(canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
 	//starting from the camera position, get the grid square, get the top left corner, keep drawin vertical lines to the left and right until we've drawn 3/4 the width of the screen each direction
 	//keep drawing horizontal lines to the top and botton until we've drawn 3/4 of the height of the screen
 	let camera = &ctx.camera_state;
@@ -669,43 +1366,172 @@ pub fn draw_grid(canvas: &mut WindowCanvas, _background_texture: &Texture, ctx:
 	}
 }
 
-pub trait BattleRenderable{
-	fn render(&self, canvas: &mut WindowCanvas, background_texture: &Texture, ctx: &BattleContext);
+pub fn draw_build_preview<T: RenderTarget>(canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+	//a see-through ghost of whatever is about to be built, green if it can go there and red if it can't
+	let camera = &ctx.camera_state;
+	let canvas_size = canvas.output_size().unwrap();
+	let site = ctx.player.build_site();
+	let color = match ctx.build_preview {
+		Some(Ok(())) => Color::RGBA(0, 255, 0, 128),
+		Some(Err(_)) => Color::RGBA(255, 0, 0, 128),
+		None => return,
+	};
+	canvas.set_blend_mode(BlendMode::Blend);
+	canvas.set_draw_color(color);
+	match site {
+		BuildSite::Edge(endpoints) => {
+			let start = endpoints.0.to_display_coord(camera.pos, camera.scale, canvas_size);
+			let end = endpoints.1.to_display_coord(camera.pos, camera.scale, canvas_size);
+			let offset = if start.x() == end.x() { (1, 0) } else { (0, 1) };
+			for thickness in -1..=1 {
+				canvas.draw_line(start.offset(offset.0 * thickness, offset.1 * thickness), end.offset(offset.0 * thickness, offset.1 * thickness)).unwrap();
+			}
+		},
+		BuildSite::Square(square) => {
+			let size = (camera.scale * GridCoord::grid_size() as f32) as u32;
+			canvas.fill_rect(Rect::from_center(square.center().to_display_coord(camera.pos, camera.scale, canvas_size), size, size)).unwrap();
+		},
+	}
+	canvas.set_blend_mode(BlendMode::None);
+}
+
+pub trait BattleRenderable<T: RenderTarget>{
+	fn layer(&self) -> RenderLayer;
+
+	fn sort_key(&self) -> i32{
+		//within a layer, lower keys are drawn first, so things further down the screen end up in front
+		0
+	}
+
+	fn bounds(&self) -> Option<Bounds>{
+		//the space taken up in the battle, None for things that cover the whole screen
+		None
+	}
+
+	fn is_shown(&self, _ctx: &BattleContext) -> bool{
+		true
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext);
+}
+
+struct Grid;
+
+impl<T: RenderTarget> BattleRenderable<T> for Grid{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Background
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		draw_grid(canvas, assets, ctx);
+	}
+}
+
+struct AlarmFlash;
+
+impl<T: RenderTarget> BattleRenderable<T> for AlarmFlash{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Overlay
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		ctx.alarm.is_some()
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, _assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		//flash the square the tripwire went off in
+		let canvas_size = canvas.output_size().unwrap();
+		if let Some((square, ticks_left)) = ctx.alarm{
+			if (ticks_left / 10) % 2 == 0{
+				canvas.set_draw_color(Color::YELLOW);
+				canvas.draw_rect(Rect::from_center(
+					square.center().to_display_coord(ctx.camera_state.pos, ctx.camera_state.scale, canvas_size),
+					(ctx.camera_state.scale * GridCoord::grid_size() as f32) as u32,
+					(ctx.camera_state.scale * GridCoord::grid_size() as f32) as u32
+				)).unwrap();
+			}
+		}
+	}
+}
+
+struct BuildPreview;
+
+impl<T: RenderTarget> BattleRenderable<T> for BuildPreview{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Overlay
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		matches!(ctx.player.state, PlayerState::BuildPlacing(_, _))
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		draw_build_preview(canvas, assets, ctx);
+	}
+}
+
+struct DefeatBanner;
+
+impl<T: RenderTarget> BattleRenderable<T> for DefeatBanner{
+	fn layer(&self) -> RenderLayer{
+		RenderLayer::Interface
+	}
+
+	fn is_shown(&self, ctx: &BattleContext) -> bool{
+		matches!(ctx.state, BattleState::Finished)
+	}
+
+	fn render(&self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, _ctx: &BattleContext){
+		let canvas_size = canvas.output_size().unwrap();
+		render_text(
+			canvas,
+			assets,
+			"Defeated",
+			96,
+			Color::RED,
+			Layout::new(canvas_size).rect(Anchor::Center, (0, 0), (400, 150))
+		);
+	}
+}
+
+impl BattleContext{
+	pub fn submit_renderables<'a, T: RenderTarget>(&'a self, queue: &mut RenderQueue<'a, T>){
+		//everything the battle draws, new kinds of things only need adding here
+		queue.submit(&Grid, self);
+		queue.submit(&self.terrain, self);
+		queue.submit_all(self.walls.iter(), self);
+		queue.submit_all(self.doors.iter(), self);
+		queue.submit_all(&self.windows, self);
+		queue.submit(&self.button, self);
+		queue.submit_all(&self.ability_plots, self);
+		queue.submit_all(self.traps.values(), self);
+		queue.submit_all(self.turrets.values(), self);
+		queue.submit(&self.fog, self);
+		queue.submit(&AlarmFlash, self);
+		queue.submit(&BuildPreview, self);
+		queue.submit(&self.player, self);
+		queue.submit_all(&self.enemies, self);
+		queue.submit_all(&self.bosses, self);
+		queue.submit_all(&self.projectiles, self);
+		queue.submit(&self.particles, self);
+		queue.submit(&self.minimap, self);
+		queue.submit(&DefeatBanner, self);
+	}
 }
 
-pub fn render_battle(canvas: &mut WindowCanvas, background_texture: &Texture, ctx: &BattleContext){
+pub fn render_battle<T: RenderTarget>(canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
 	let canvas_size = canvas.output_size().unwrap();
 	canvas.clear();
 	canvas.set_draw_color(Color::RGB(0,0,16));
 	canvas.fill_rect(Rect::new(0,0, canvas_size.0, canvas_size.1)).unwrap();
-	//canvas.copy(background_texture, None, None).expect("Couldn't draw background texture.");
-	draw_grid(canvas, background_texture, ctx);
-
-	for wall in &ctx.walls{
-		wall.render(canvas, background_texture, ctx);
-	}
-	for visible_grid_square in ctx.get_visible_squares(){
-		canvas.set_draw_color(Color::RGB(32, 32, 32));
-		canvas.fill_rect(
-			Rect::from_center(
-				visible_grid_square.center().to_display_coord(
-					ctx.camera_state.pos,
-					ctx.camera_state.scale,
-					canvas_size
-				),
-				(GridCoord::grid_size() as f32 * 0.6) as u32,
-				(GridCoord::grid_size() as f32 * 0.6) as u32
-			)
-		).unwrap();
-	}
-	ctx.button.render(canvas, background_texture, ctx);
-	for ability_plot in &ctx.ability_plots{
-		ability_plot.render(canvas, background_texture, ctx);
-	}
-	ctx.player.render(canvas, background_texture, ctx);
-	for enemy in &ctx.enemies{
-		enemy.render(canvas, background_texture, ctx);
-	}
-	Hud::from_player(&ctx.player).render(canvas, background_texture, ctx);
+	//canvas.copy(assets.texture("assets/images/button_game_splash_art.png"), None, None).expect("Couldn't draw background texture.");
+	let mut hud = Hud::from_player(&ctx.player);
+	hud.boss_health = ctx.bosses.first().map(|boss| boss.health);
+	hud.alarm = ctx.alarm.is_some();
+	hud.build_feedback = ctx.build_feedback.as_ref().map(|(reason, _)| reason.clone());
+	let mut queue = RenderQueue::new(&ctx.camera_state, canvas_size);
+	queue.submit(&hud, ctx);
+	ctx.submit_renderables(&mut queue);
+	queue.draw(canvas, assets, ctx);
 	canvas.present();
 }
\ No newline at end of file
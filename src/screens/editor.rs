@@ -0,0 +1,329 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::ability_plots::AbilityPlot;
+use crate::battle_objects::arena::{ArenaLayout, SpawnPoint};
+use crate::battle_objects::battle_player::Ability;
+use crate::battle_objects::buildables::{Wall, WallMaterial, Window};
+use crate::battle_objects::camera::CameraState;
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::enemy::EnemyKind;
+use crate::battle_objects::terrain::Terrain;
+use crate::game_context::{GameContext, GameObject};
+use crate::input::InputState;
+use crate::screens::battle::{draw_grid, BattleContext, BattleRenderable};
+use crate::sound_manager::SoundManager;
+use crate::utils::arena_generation::everything_reaches_button;
+use crate::utils::map_format::{load_layout, save_layout};
+use crate::utils::layout::{Anchor, Layout};
+use crate::utils::render_utils::render_text;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditorTool{
+	Wall,
+	Window,
+	AbilityPlot,
+	Button,
+	SpawnPoint,
+	Terrain,
+}
+
+impl EditorTool{
+	fn next(&self) -> EditorTool{
+		match self {
+			EditorTool::Wall => EditorTool::Window,
+			EditorTool::Window => EditorTool::AbilityPlot,
+			EditorTool::AbilityPlot => EditorTool::Button,
+			EditorTool::Button => EditorTool::SpawnPoint,
+			EditorTool::SpawnPoint => EditorTool::Terrain,
+			EditorTool::Terrain => EditorTool::Wall,
+		}
+	}
+
+	fn previous(&self) -> EditorTool{
+		match self {
+			EditorTool::Wall => EditorTool::Terrain,
+			EditorTool::Window => EditorTool::Wall,
+			EditorTool::AbilityPlot => EditorTool::Window,
+			EditorTool::Button => EditorTool::AbilityPlot,
+			EditorTool::SpawnPoint => EditorTool::Button,
+			EditorTool::Terrain => EditorTool::SpawnPoint,
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct EditorContext{
+	pub layout: ArenaLayout,
+	pub cursor: GridCoord,
+	pub cursor_side: Direction, //which edge of the cursor square walls and windows are painted on
+	pub tool: EditorTool,
+	pub plot_ability: Ability,
+	pub spawn_kind: EnemyKind,
+	pub terrain_kind: Terrain,
+	pub camera_state: CameraState,
+	pub input_cooldown: u32,
+	pub status: Option<(String, u32)>, //message and how many more ticks to show it for
+	pub preview: BattleContext, //the layout as a battle would start on it, rebuilt only when the layout changes
+}
+
+impl EditorContext{
+	pub fn map_path() -> &'static str{
+		"maps/custom.map"
+	}
+
+	pub fn new() -> EditorContext{
+		//pick up where the last session left off, if there was one
+		let layout = match load_layout(EditorContext::map_path()) {
+			Ok(layout) => layout,
+			Err(why) => {
+				println!("Starting a new map, couldn't load {}: {}", EditorContext::map_path(), why);
+				ArenaLayout::classic()
+			}
+		};
+		EditorContext{
+			cursor: layout.button,
+			preview: BattleContext::from_layout(&layout),
+			layout,
+			cursor_side: Direction::North,
+			tool: EditorTool::Wall,
+			plot_ability: Ability::MeleeAttack,
+			spawn_kind: EnemyKind::Melee,
+			terrain_kind: Terrain::Mud,
+			camera_state: CameraState::new(),
+			input_cooldown: 0,
+			status: None,
+		}
+	}
+
+	fn input_repeat_delay() -> u32{
+		8
+	}
+
+	fn set_status(&mut self, message: String){
+		println!("{}", message);
+		self.status = Some((message, 90));
+	}
+
+	fn refresh_preview(&mut self){
+		let mut preview = BattleContext::from_layout(&self.layout);
+		preview.camera_state = self.camera_state.clone();
+		self.preview = preview;
+	}
+
+	fn cursor_edge(&self) -> (GameCoord, GameCoord){
+		self.cursor.edge(self.cursor_side)
+	}
+
+	fn place(&mut self){
+		let edge = self.cursor_edge();
+		let same_edge = |endpoints: &(GameCoord, GameCoord)| *endpoints == edge || *endpoints == (edge.1, edge.0);
+		match self.tool {
+			EditorTool::Wall => {
+				self.layout.windows.retain(|window| !same_edge(&window.endpoints));
+				if !self.layout.walls.iter().any(|wall| same_edge(&wall.endpoints)) {
+					self.layout.walls.push(Wall::new(edge, WallMaterial::Stone));
+				}
+			},
+			EditorTool::Window => {
+				self.layout.walls.retain(|wall| !same_edge(&wall.endpoints));
+				if !self.layout.windows.iter().any(|window| same_edge(&window.endpoints)) {
+					self.layout.windows.push(Window{ endpoints: edge, health: (50, 50) });
+				}
+			},
+			EditorTool::AbilityPlot => {
+				let cursor = self.cursor;
+				self.layout.ability_plots.retain(|plot| plot.pos != cursor);
+				self.layout.ability_plots.push(AbilityPlot{ pos: cursor, ability: self.plot_ability });
+			},
+			EditorTool::Button => {
+				self.layout.button = self.cursor;
+			},
+			EditorTool::SpawnPoint => {
+				let cursor = self.cursor;
+				self.layout.spawn_points.retain(|spawn| spawn.pos != cursor);
+				self.layout.spawn_points.push(SpawnPoint{ pos: cursor, kind: self.spawn_kind });
+			},
+			EditorTool::Terrain => {
+				let cursor = self.cursor;
+				let occupied = self.layout.button == cursor
+					|| self.layout.player_start.to_grid_coord() == cursor
+					|| self.layout.spawn_points.iter().any(|spawn| spawn.pos == cursor);
+				if occupied && !self.terrain_kind.is_passable() {
+					self.set_status(String::from("Can't flood the player start, a spawn point or the button"));
+					return;
+				}
+				self.layout.terrain.set(cursor, self.terrain_kind);
+			},
+		}
+		self.refresh_preview();
+	}
+
+	fn erase(&mut self){
+		let edge = self.cursor_edge();
+		let same_edge = |endpoints: &(GameCoord, GameCoord)| *endpoints == edge || *endpoints == (edge.1, edge.0);
+		let cursor = self.cursor;
+		match self.tool {
+			EditorTool::Wall => self.layout.walls.retain(|wall| !same_edge(&wall.endpoints)),
+			EditorTool::Window => self.layout.windows.retain(|window| !same_edge(&window.endpoints)),
+			EditorTool::AbilityPlot => self.layout.ability_plots.retain(|plot| plot.pos != cursor),
+			EditorTool::Button => self.set_status(String::from("The button can be moved, but not removed")),
+			EditorTool::SpawnPoint => self.layout.spawn_points.retain(|spawn| spawn.pos != cursor),
+			EditorTool::Terrain => self.layout.terrain.set(cursor, Terrain::Floor),
+		}
+		self.refresh_preview();
+	}
+
+	fn cycle_option(&mut self){
+		match self.tool {
+			EditorTool::Wall | EditorTool::Window => {
+				self.cursor_side = match self.cursor_side {
+					Direction::North => Direction::East,
+					Direction::East => Direction::South,
+					Direction::South => Direction::West,
+					Direction::West => Direction::North,
+				};
+			},
+			EditorTool::AbilityPlot => {
+				let abilities = Ability::all();
+				let current_index = abilities.iter().position(|ability| *ability == self.plot_ability).unwrap_or(0);
+				self.plot_ability = abilities[(current_index + 1) % abilities.len()];
+			},
+			EditorTool::Button => (),
+			EditorTool::SpawnPoint => {
+				self.spawn_kind = match self.spawn_kind {
+					EnemyKind::Melee => EnemyKind::Ranged,
+					EnemyKind::Ranged => EnemyKind::Melee,
+				};
+			},
+			EditorTool::Terrain => {
+				let kinds = Terrain::all();
+				let current_index = kinds.iter().position(|terrain| *terrain == self.terrain_kind).unwrap_or(0);
+				self.terrain_kind = kinds[(current_index + 1) % kinds.len()];
+			},
+		}
+	}
+
+	fn save(&mut self){
+		match save_layout(&self.layout, EditorContext::map_path()) {
+			Ok(()) if everything_reaches_button(&self.layout, &self.layout.walls) => {
+				self.set_status(format!("Saved to {}", EditorContext::map_path()));
+			},
+			Ok(()) => {
+				self.set_status(format!("Saved to {}, but some plots or spawns can't reach the button", EditorContext::map_path()));
+			},
+			Err(why) => self.set_status(format!("Couldn't save map: {}", why)),
+		}
+	}
+
+	fn get_tool_text(&self) -> String{
+		match self.tool {
+			EditorTool::Wall => format!("Wall ({:?} edge)", self.cursor_side),
+			EditorTool::Window => format!("Window ({:?} edge)", self.cursor_side),
+			EditorTool::AbilityPlot => format!("Plot: {}", self.plot_ability.get_hud_text()),
+			EditorTool::Button => String::from("Button"),
+			EditorTool::SpawnPoint => format!("Spawn: {:?}", self.spawn_kind),
+			EditorTool::Terrain => format!("Terrain: {:?}", self.terrain_kind),
+		}
+	}
+
+	pub fn handle_tick(game_obj: &mut GameObject, input_state: &InputState, _sound_manager: &mut SoundManager){
+		let GameContext::Editor(editor) = &mut game_obj.phase else { unreachable!("Game object is not in Editor phase") };
+		editor.camera_state.smooth_scroll(&editor.cursor.center());
+		editor.preview.camera_state = editor.camera_state.clone();
+		if let Some((message, ticks_left)) = &editor.status {
+			editor.status = if *ticks_left > 0 { Some((message.clone(), ticks_left - 1)) } else { None };
+		}
+
+		let any_input = input_state.dpad_up || input_state.dpad_down || input_state.dpad_left || input_state.dpad_right ||
+				input_state.btn_down || input_state.btn_right || input_state.btn_left || input_state.btn_up ||
+				input_state.left_shoulder || input_state.right_shoulder || input_state.btn_start;
+		if !any_input {
+			//releasing everything lets the next press through straight away
+			editor.input_cooldown = 0;
+			return;
+		}
+		if editor.input_cooldown > 0 {
+			editor.input_cooldown -= 1;
+			return;
+		}
+		editor.input_cooldown = EditorContext::input_repeat_delay();
+
+		if input_state.dpad_up { editor.cursor = editor.cursor.to_north(1); }
+		if input_state.dpad_down { editor.cursor = editor.cursor.to_south(1); }
+		if input_state.dpad_left { editor.cursor = editor.cursor.to_west(1); }
+		if input_state.dpad_right { editor.cursor = editor.cursor.to_east(1); }
+		if input_state.left_shoulder { editor.tool = editor.tool.previous(); }
+		if input_state.right_shoulder { editor.tool = editor.tool.next(); }
+		if input_state.btn_left { editor.cycle_option(); }
+		if input_state.btn_down { editor.place(); }
+		if input_state.btn_right { editor.erase(); }
+		if input_state.btn_up { editor.save(); }
+		if input_state.btn_start {
+			//save and jump straight into a battle on this map
+			editor.save();
+			let layout = editor.layout.clone();
+			game_obj.phase = GameContext::Battle(BattleContext::from_layout(&layout));
+		}
+	}
+}
+
+pub fn render_editor<T: RenderTarget>(canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, context: &EditorContext){
+	let canvas_size = canvas.output_size().unwrap();
+	canvas.clear();
+	canvas.set_draw_color(Color::RGB(0,0,16));
+	canvas.fill_rect(Rect::new(0,0, canvas_size.0, canvas_size.1)).unwrap();
+
+	//preview the map with the same renderers the battle uses
+	let preview = &context.preview;
+	draw_grid(canvas, assets, preview);
+	preview.terrain.render(canvas, assets, preview);
+	for wall in preview.walls.iter(){
+		wall.render(canvas, assets, preview);
+	}
+	for window in &preview.windows{
+		window.render(canvas, assets, preview);
+	}
+	preview.button.render(canvas, assets, preview);
+	for ability_plot in &preview.ability_plots{
+		ability_plot.render(canvas, assets, preview);
+	}
+	for enemy in &preview.enemies{
+		enemy.render(canvas, assets, preview);
+	}
+
+	let camera = &context.camera_state;
+	canvas.set_blend_mode(BlendMode::Blend);
+	canvas.set_draw_color(Color::RGBA(255, 255, 255, 64));
+	let cursor_rect = Rect::from_center(
+		context.cursor.center().to_display_coord(camera.pos, camera.scale, canvas_size),
+		(camera.scale * GridCoord::grid_size() as f32) as u32,
+		(camera.scale * GridCoord::grid_size() as f32) as u32
+	);
+	canvas.fill_rect(cursor_rect).unwrap();
+	canvas.set_blend_mode(BlendMode::None);
+	if let EditorTool::Wall | EditorTool::Window = context.tool {
+		let edge = context.cursor.edge(context.cursor_side);
+		canvas.set_draw_color(Color::YELLOW);
+		canvas.draw_line(
+			edge.0.to_display_coord(camera.pos, camera.scale, canvas_size),
+			edge.1.to_display_coord(camera.pos, camera.scale, canvas_size)
+		).unwrap();
+	}
+
+	let layout = Layout::new(canvas_size);
+	render_text(canvas, assets, &context.get_tool_text(), 32, Color::WHITE, layout.rect(Anchor::TopLeft, (10, 10), (300, 40)));
+	render_text(
+		canvas,
+		assets,
+		"Move: D-pad  Tool: LB/RB  Option: X  Place: A  Erase: B  Save: Y  Play: Start",
+		24,
+		Color::GRAY,
+		layout.rect(Anchor::BottomLeft, (10, 10), (700, 30))
+	);
+	if let Some((message, _)) = &context.status {
+		render_text(canvas, assets, message, 24, Color::YELLOW, layout.rect(Anchor::TopLeft, (10, 56), (500, 30)));
+	}
+	canvas.present();
+}
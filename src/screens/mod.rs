@@ -1,2 +1,6 @@
 pub mod battle;
-pub mod start;
\ No newline at end of file
+pub mod editor;
+pub mod render_queue;
+pub mod start;
+#[cfg(test)]
+mod render_tests;
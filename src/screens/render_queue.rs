@@ -0,0 +1,92 @@
+use sdl2::render::{Canvas, RenderTarget};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::camera::CameraState;
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::screens::battle::{BattleContext, BattleRenderable};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer{
+	//drawn first to last
+	Background,
+	Terrain,
+	Structures, //walls, doors and windows
+	Floor, //anything sitting in a square that bodies walk over or around
+	Fog,
+	Overlay, //markers that have to show through the fog
+	Bodies,
+	Effects,
+	Interface, //screen space, never culled
+}
+
+pub type Bounds = (GameCoord, GameCoord); //top left and bottom right in game units
+
+pub fn bounding_box(first: GameCoord, second: GameCoord) -> Bounds{
+	(
+		GameCoord{x: first.x.min(second.x), y: first.y.min(second.y)},
+		GameCoord{x: first.x.max(second.x), y: first.y.max(second.y)}
+	)
+}
+
+pub fn box_around(center: GameCoord, width: u32) -> Bounds{
+	let half = (width / 2) as i32;
+	(GameCoord{x: center.x - half, y: center.y - half}, GameCoord{x: center.x + half, y: center.y + half})
+}
+
+struct DrawCommand<'a, T: RenderTarget>{
+	layer: RenderLayer,
+	sort_key: i32,
+	renderable: &'a dyn BattleRenderable<T>,
+}
+
+pub struct RenderQueue<'a, T: RenderTarget>{
+	//everything submitted this frame, drawn in one go once the whole battle has been gathered
+	commands: Vec<DrawCommand<'a, T>>,
+	view: Bounds, //the part of the battle the camera can see, plus a square of slack
+}
+
+impl<'a, T: RenderTarget> RenderQueue<'a, T>{
+	pub fn new(camera: &CameraState, canvas_size: (u32, u32)) -> RenderQueue<'a, T>{
+		let slack = GridCoord::grid_size();
+		let half_width = (canvas_size.0 as f32 / 2.0 / camera.scale) as i32 + slack;
+		let half_height = (canvas_size.1 as f32 / 2.0 / camera.scale) as i32 + slack;
+		RenderQueue{
+			commands: Vec::new(),
+			view: (
+				GameCoord{x: camera.pos.x - half_width, y: camera.pos.y - half_height},
+				GameCoord{x: camera.pos.x + half_width, y: camera.pos.y + half_height}
+			),
+		}
+	}
+
+	fn in_view(&self, bounds: Bounds) -> bool{
+		let (top_left, bottom_right) = bounds;
+		bottom_right.x >= self.view.0.x && top_left.x <= self.view.1.x
+				&& bottom_right.y >= self.view.0.y && top_left.y <= self.view.1.y
+	}
+
+	pub fn submit(&mut self, renderable: &'a dyn BattleRenderable<T>, ctx: &BattleContext){
+		if !renderable.is_shown(ctx) {
+			return;
+		}
+		if let Some(bounds) = renderable.bounds() {
+			if !self.in_view(bounds) {
+				return;
+			}
+		}
+		self.commands.push(DrawCommand{ layer: renderable.layer(), sort_key: renderable.sort_key(), renderable });
+	}
+
+	pub fn submit_all<R: BattleRenderable<T> + 'a>(&mut self, renderables: impl IntoIterator<Item = &'a R>, ctx: &BattleContext){
+		for renderable in renderables {
+			self.submit(renderable, ctx);
+		}
+	}
+
+	pub fn draw(mut self, canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, ctx: &BattleContext){
+		//the sort is stable, so anything on the same layer and key keeps the order it was submitted in
+		self.commands.sort_by_key(|command| (command.layer, command.sort_key));
+		for command in &self.commands {
+			command.renderable.render(canvas, assets, ctx);
+		}
+	}
+}
@@ -0,0 +1,209 @@
+//golden image tests for the battle renderer
+//every fixture is drawn onto a plain software surface with the dummy video driver, so no window is ever opened
+//the SDL ones are ignored by default since they need SDL2, SDL2_image and SDL2_ttf installed:
+//  cargo test -- --ignored                      compare against tests/golden
+//  UPDATE_GOLDEN=1 cargo test -- --ignored      rewrite tests/golden from whatever renders now
+use std::path::PathBuf;
+use std::sync::Mutex;
+use sdl2::image::{LoadSurface, SaveSurface};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::{Surface, SurfaceRef};
+use crate::asset_cache::AssetCache;
+use crate::battle_objects::buildables::{Door, Wall, WallMaterial};
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::battle_objects::enemy::{Enemy, EnemyKind};
+use crate::battle_objects::traps::{Trap, TrapKind};
+use crate::battle_objects::turret::Turret;
+use crate::screens::battle::{render_battle, BattleContext};
+
+//SDL can only be alive on one thread at a time, and the test runner uses several
+static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug)]
+struct Image{
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>, //RGBA, tightly packed
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Tolerance{
+	channel: u8, //how far apart two channels can be before the pixel counts as different
+	max_different_pixels: f32, //fraction of the image allowed to differ, for font hinting and the like
+}
+
+impl Tolerance{
+	fn default() -> Tolerance{
+		Tolerance{ channel: 8, max_different_pixels: 0.002 }
+	}
+}
+
+fn fixture_size() -> (u32, u32){
+	(540, 360)
+}
+
+fn golden_path(name: &str) -> PathBuf{
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name))
+}
+
+fn failure_path(name: &str) -> PathBuf{
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target").join("golden-failures").join(format!("{}.png", name))
+}
+
+fn surface_image(surface: &SurfaceRef) -> Image{
+	//rows can be padded out past the pixels, so copy them one at a time
+	let surface = surface.convert_format(PixelFormatEnum::RGBA32).unwrap();
+	let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+	let pixels = surface.with_lock(|raw| {
+		(0..height as usize)
+				.flat_map(|row| raw[row * pitch..row * pitch + width as usize * 4].iter().copied())
+				.collect()
+	});
+	Image{ width, height, pixels }
+}
+
+fn render_fixture(ctx: &BattleContext) -> Surface<'static>{
+	sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+	let sdl_context = sdl2::init().unwrap();
+	let _video = sdl_context.video().unwrap();
+	let ttf_context = sdl2::ttf::init().unwrap();
+	let (width, height) = fixture_size();
+	let mut canvas = Surface::new(width, height, PixelFormatEnum::RGBA32).unwrap().into_canvas().unwrap();
+	let texture_creator = canvas.texture_creator();
+	{
+		let mut assets = AssetCache::new(&texture_creator, &ttf_context);
+		render_battle(&mut canvas, &mut assets, ctx);
+	}
+	canvas.into_surface()
+}
+
+fn compare_images(actual: &Image, expected: &Image, tolerance: Tolerance) -> Result<(), String>{
+	if (actual.width, actual.height) != (expected.width, expected.height) {
+		return Err(format!("size is {}x{}, expected {}x{}", actual.width, actual.height, expected.width, expected.height));
+	}
+	let different = actual.pixels.chunks(4)
+			.zip(expected.pixels.chunks(4))
+			.filter(|(first, second)| first.iter().zip(second.iter()).any(|(a, b)| a.abs_diff(*b) > tolerance.channel))
+			.count();
+	let allowed = (tolerance.max_different_pixels * (actual.width * actual.height) as f32) as usize;
+	if different > allowed {
+		return Err(format!("{} pixels differ, at most {} allowed", different, allowed));
+	}
+	Ok(())
+}
+
+fn assert_matches_golden(name: &str, ctx: &BattleContext){
+	let _lock = SDL_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	let rendered = render_fixture(ctx);
+	let golden = golden_path(name);
+	if std::env::var("UPDATE_GOLDEN").is_ok() {
+		std::fs::create_dir_all(golden.parent().unwrap()).unwrap();
+		rendered.save(&golden).unwrap();
+		println!("Updated golden image {}", golden.display());
+		return;
+	}
+	let result = match Surface::from_file(&golden) {
+		Ok(expected) => compare_images(&surface_image(&rendered), &surface_image(&expected), Tolerance::default()),
+		Err(error) => Err(format!("no golden image at {} ({}), run with UPDATE_GOLDEN=1 to create it", golden.display(), error)),
+	};
+	if let Err(reason) = result {
+		let failure = failure_path(name);
+		std::fs::create_dir_all(failure.parent().unwrap()).unwrap();
+		rendered.save(&failure).unwrap();
+		panic!("{} doesn't match its golden image: {}. What rendered is at {}", name, reason, failure.display());
+	}
+}
+
+fn settled(mut ctx: BattleContext) -> BattleContext{
+	//what a battle looks like once the camera has caught up with the player
+	ctx.update_fog();
+	ctx.camera_state.focus = ctx.player.game_coord;
+	ctx.camera_state.pos = ctx.player.game_coord;
+	ctx
+}
+
+fn classic_start() -> BattleContext{
+	settled(BattleContext::new())
+}
+
+fn fortified_base() -> BattleContext{
+	let mut ctx = BattleContext::new();
+	let base = ctx.player.game_coord.to_grid_coord();
+	ctx.walls.insert(Wall::new(base.offset((0, -1)).edge(Direction::North), WallMaterial::Wood));
+	ctx.walls.insert(Wall::new(base.offset((1, -1)).edge(Direction::North), WallMaterial::Stone));
+	ctx.walls.insert(Wall::new(base.offset((2, -1)).edge(Direction::North), WallMaterial::Reinforced));
+	ctx.doors.insert(Door::new(base.offset((-1, -1)).edge(Direction::North)));
+	ctx.turrets.insert(base.offset((2, 1)), Turret::new(base.offset((2, 1))));
+	ctx.traps.insert(base.offset((-2, 1)), Trap::new(base.offset((-2, 1)), TrapKind::Spikes));
+	ctx.enemies.push(Enemy::new(base.offset((3, 2)).center(), EnemyKind::Melee));
+	ctx.enemies.push(Enemy::new(base.offset((-3, 2)).center(), EnemyKind::Ranged));
+	settled(ctx)
+}
+
+fn remembered_fog() -> BattleContext{
+	//look around from the start, then walk off so what was seen is only remembered
+	let mut ctx = BattleContext::new();
+	ctx.update_fog();
+	ctx.player.game_coord = GameCoord{x: ctx.player.game_coord.x + 4 * GridCoord::grid_size(), y: ctx.player.game_coord.y};
+	settled(ctx)
+}
+
+#[test]
+#[ignore = "needs the SDL2 libraries"]
+fn classic_start_matches_golden(){
+	assert_matches_golden("classic_start", &classic_start());
+}
+
+#[test]
+#[ignore = "needs the SDL2 libraries"]
+fn fortified_base_matches_golden(){
+	assert_matches_golden("fortified_base", &fortified_base());
+}
+
+#[test]
+#[ignore = "needs the SDL2 libraries"]
+fn remembered_fog_matches_golden(){
+	assert_matches_golden("remembered_fog", &remembered_fog());
+}
+
+fn solid(width: u32, height: u32, pixel: [u8; 4]) -> Image{
+	Image{ width, height, pixels: pixel.repeat((width * height) as usize) }
+}
+
+#[test]
+fn identical_images_match(){
+	let image = solid(10, 10, [10, 20, 30, 255]);
+	assert!(compare_images(&image, &image, Tolerance::default()).is_ok());
+}
+
+#[test]
+fn small_channel_differences_are_tolerated(){
+	let expected = solid(10, 10, [10, 20, 30, 255]);
+	let actual = solid(10, 10, [14, 16, 30, 255]);
+	assert!(compare_images(&actual, &expected, Tolerance::default()).is_ok());
+}
+
+#[test]
+fn a_few_stray_pixels_are_tolerated(){
+	let expected = solid(100, 100, [0, 0, 0, 255]);
+	let mut actual = solid(100, 100, [0, 0, 0, 255]);
+	actual.pixels[0..8].copy_from_slice(&[255, 255, 255, 255, 255, 255, 255, 255]);
+	assert!(compare_images(&actual, &expected, Tolerance::default()).is_ok());
+}
+
+#[test]
+fn changed_region_fails(){
+	let expected = solid(100, 100, [0, 0, 0, 255]);
+	let mut actual = solid(100, 100, [0, 0, 0, 255]);
+	for pixel in actual.pixels.chunks_mut(4).take(500) {
+		pixel.copy_from_slice(&[255, 0, 0, 255]);
+	}
+	assert!(compare_images(&actual, &expected, Tolerance::default()).is_err());
+}
+
+#[test]
+fn size_mismatch_fails(){
+	let expected = solid(10, 10, [0, 0, 0, 255]);
+	let actual = solid(10, 12, [0, 0, 0, 255]);
+	assert!(compare_images(&actual, &expected, Tolerance::default()).is_err());
+}
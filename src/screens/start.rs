@@ -1,10 +1,12 @@
 use sdl2::pixels::Color;
-use sdl2::rect::{Rect};
-use sdl2::render::{WindowCanvas, Texture, BlendMode};
+use sdl2::render::{Canvas, RenderTarget, BlendMode};
+use crate::asset_cache::AssetCache;
 use crate::game_context::{GameContext, GameObject};
 use crate::input::{InputState};
 use crate::screens::battle::BattleContext;
+use crate::screens::editor::EditorContext;
 use crate::sound_manager::SoundManager;
+use crate::utils::layout::{Anchor, Layout};
 use crate::utils::render_utils::render_text;
 
 #[derive(Clone, Copy)]
@@ -36,11 +38,14 @@ impl StartScreenContext{
 			StartScreenState::FadeIn(a,b) => {
 				game_obj.phase = GameContext::StartScreen(StartScreenContext{state: StartScreenState::FadeIn(a+1, b)})
 			},
-			StartScreenState::Waiting => match input_state.btn_start{
-				true => {
+			StartScreenState::Waiting => match (input_state.btn_start, input_state.btn_back){
+				(true, _) => {
 					game_obj.phase = GameContext::StartScreen(StartScreenContext{state: StartScreenState::FadeOut(0,30)})
 				},
-				false => (),
+				(false, true) => {
+					game_obj.phase = GameContext::Editor(EditorContext::new())
+				},
+				(false, false) => (),
 			},
 			StartScreenState::FadeOut(a,b) if a==b => {
 				game_obj.phase = GameContext::Battle(BattleContext::from_game_object(game_obj))
@@ -53,39 +58,47 @@ impl StartScreenContext{
 	}
 }
 
-pub fn render_start_screen(canvas: &mut WindowCanvas, background_texture: &Texture, context: &StartScreenContext){
-	let (width, height) = canvas.output_size().unwrap();
+pub fn render_start_screen<T: RenderTarget>(canvas: &mut Canvas<T>, assets: &mut AssetCache<T::Context>, context: &StartScreenContext){
+	let layout = Layout::new(canvas.output_size().unwrap());
 	canvas.clear();
-	canvas.copy(background_texture, None, None).expect("Couldn't draw background texture.");
+	canvas.copy(assets.texture("assets/images/button_game_splash_art.png"), None, None).expect("Couldn't draw background texture.");
 
-	let ttf_context = sdl2::ttf::init().unwrap();
 
 	render_text(
 		canvas,
-		&ttf_context,
+		assets,
 		"Button Game",
 		128,
 		Color::YELLOW,
-		Rect::new(50, 100, (width as f32 *0.8) as u32, (height as f32 *0.25) as u32)
+		layout.rect(Anchor::TopLeft, (50, 100), (864, 180))
 	);
 
 	// Render the text into a surface.
 	render_text(
 		canvas,
-		&ttf_context,
+		assets,
 		"Survival",
 		64,
 		Color::YELLOW,
-		Rect::new(50, 250, 400, 100)
+		layout.rect(Anchor::TopLeft, (50, 250), (400, 100))
 	);
 
 	render_text(
 		canvas,
-		&ttf_context,
+		assets,
 		"Press Start",
 		64,
 		Color::WHITE,
-		Rect::new(((width / 2)-100) as i32, ((height/2)+50) as i32, 200, 100)
+		layout.rect(Anchor::Center, (0, 100), (200, 100))
+	);
+
+	render_text(
+		canvas,
+		assets,
+		"Back: Level Editor",
+		32,
+		Color::GRAY,
+		layout.rect(Anchor::Center, (0, 185), (200, 50))
 	);
 
 	match context.state{
@@ -94,14 +107,14 @@ pub fn render_start_screen(canvas: &mut WindowCanvas, background_texture: &Textu
 			canvas.set_blend_mode(BlendMode::Blend);
 			let opacity: u8 = (255 * (frame_max - frame_num) / frame_max) as u8;
 			canvas.set_draw_color((0, 0, 0, opacity));
-			canvas.fill_rect(Rect::new(0, 0, width, height)).expect("Failed to draw a rectangle");
+			canvas.fill_rect(layout.fill()).expect("Failed to draw a rectangle");
 			canvas.set_blend_mode(BlendMode::None);//put the blend mode back to normal
 		},
 		StartScreenState::FadeOut(frame_num, frame_max) =>{
 			canvas.set_blend_mode(BlendMode::Blend);
 			let opacity: u8 = (255 * frame_num / frame_max) as u8;
 			canvas.set_draw_color((255,255,255,opacity));
-			canvas.fill_rect(Rect::new(0, 0, width, height)).expect("Failed to draw a rectangle");
+			canvas.fill_rect(layout.fill()).expect("Failed to draw a rectangle");
 			canvas.set_blend_mode(BlendMode::None);//put the blend mode back to normal
 		},
 	}
@@ -0,0 +1,186 @@
+use crate::battle_objects::ability_plots::AbilityPlot;
+use crate::battle_objects::arena::{ArenaLayout, SpawnPoint};
+use crate::battle_objects::battle_player::Ability::{Armor, Build, ButtonPress, Heal, MeleeAttack, RangeAttack, Repair, Vision};
+use crate::battle_objects::buildables::{Wall, WallMaterial, WallStore};
+use crate::battle_objects::coordinates::{Direction, GridCoord};
+use crate::battle_objects::enemy::EnemyKind;
+use crate::battle_objects::terrain::{Terrain, TerrainMap};
+use crate::utils::pathing::is_reachable;
+use crate::utils::random::SeededRng;
+
+fn random_square_at_distance(rng: &mut SeededRng, center: GridCoord, min_distance: i32, max_distance: i32) -> GridCoord{
+	loop {
+		let offset = (rng.range(-max_distance, max_distance), rng.range(-max_distance, max_distance));
+		let candidate = center.offset(offset);
+		let distance = candidate.pythagorean_distance_to(&center);
+		if distance >= min_distance as f32 && distance <= max_distance as f32 {
+			return candidate;
+		}
+	}
+}
+
+fn random_ruin(rng: &mut SeededRng, start: GridCoord) -> Vec<Wall>{
+	//a broken run of wall, sometimes with a corner, like the remains of an old building
+	let mut ruin = Vec::new();
+	let horizontal = rng.chance(0.5);
+	let length = rng.range(2, 5);
+	let mut square = start;
+	for _ in 0..length {
+		if rng.chance(0.8) {
+			let side = if horizontal { Direction::North } else { Direction::West };
+			ruin.push(Wall{ endpoints: square.edge(side), health: (rng.range(30, 100) as usize, 100), material: WallMaterial::Stone, spent: 0 });
+		}
+		square = if horizontal { square.to_east(1) } else { square.to_south(1) };
+	}
+	if rng.chance(0.4) {
+		let side = if horizontal { Direction::West } else { Direction::North };
+		ruin.push(Wall{ endpoints: start.edge(side), health: (rng.range(30, 100) as usize, 100), material: WallMaterial::Stone, spent: 0 });
+	}
+	ruin
+}
+
+fn random_patch(rng: &mut SeededRng, center: GridCoord) -> Vec<GridCoord>{
+	//a rough blob of squares around the center
+	let radius = rng.range(1, 2);
+	let mut patch = Vec::new();
+	for x in -radius..=radius {
+		for y in -radius..=radius {
+			if x*x + y*y <= radius*radius && rng.chance(0.85) {
+				patch.push(center.offset((x, y)));
+			}
+		}
+	}
+	patch
+}
+
+pub fn everything_reaches_button(layout: &ArenaLayout, walls: &Vec<Wall>) -> bool{
+	let walls = WallStore::from_items(walls.iter().copied());
+	let mut checkpoints = layout.ability_plots
+			.iter()
+			.map(|plot| plot.pos)
+			.chain(layout.spawn_points.iter().map(|spawn| spawn.pos))
+			.chain(std::iter::once(layout.player_start.to_grid_coord()));
+	//path_to gives up after a few hundred squares, which is fine for enemies but not for deciding what's fair
+	let can_step = |from: GridCoord, to: GridCoord| layout.terrain.get(to).is_passable() && !walls.is_blocking(from, to);
+	checkpoints.all(|checkpoint| is_reachable(checkpoint, layout.button, can_step))
+}
+
+pub fn generate_arena(seed: u64) -> ArenaLayout{
+	let mut rng = SeededRng::new(seed);
+	let button = GridCoord{x: rng.range(-3, 3), y: rng.range(-3, 3)};
+	let mut taken_squares = vec![button];
+
+	//scatter one plot per ability close enough to the button that learning them is a real choice
+	let mut ability_plots = Vec::new();
+	for ability in [MeleeAttack, Armor, RangeAttack, Vision, Build, Repair, ButtonPress, Heal] {
+		let mut pos = random_square_at_distance(&mut rng, button, 2, 5);
+		while taken_squares.contains(&pos) {
+			pos = random_square_at_distance(&mut rng, button, 2, 5);
+		}
+		taken_squares.push(pos);
+		ability_plots.push(AbilityPlot{ pos, ability });
+	}
+
+	let mut player_start = random_square_at_distance(&mut rng, button, 3, 4);
+	while taken_squares.contains(&player_start) {
+		player_start = random_square_at_distance(&mut rng, button, 3, 4);
+	}
+	taken_squares.push(player_start);
+
+	//enemies come in from a few zones around the edge of the arena
+	let mut spawn_points = Vec::new();
+	for _ in 0..rng.range(2, 4) {
+		let zone_center = random_square_at_distance(&mut rng, button, 11, 14);
+		for _ in 0..rng.range(1, 3) {
+			let pos = zone_center.offset((rng.range(-1, 1), rng.range(-1, 1)));
+			if taken_squares.contains(&pos) {
+				continue;
+			}
+			taken_squares.push(pos);
+			let kind = if rng.chance(0.3) { EnemyKind::Ranged } else { EnemyKind::Melee };
+			spawn_points.push(SpawnPoint{ pos, kind });
+		}
+	}
+
+	let mut layout = ArenaLayout{
+		button,
+		player_start: player_start.center(),
+		ability_plots,
+		walls: Vec::new(),
+		windows: Vec::new(),
+		spawn_points,
+		terrain: TerrainMap::new(),
+	};
+
+	//patches of rough ground, kept off anything placed so far and never cutting anything off from the button
+	for _ in 0..rng.range(2, 5) {
+		let center = random_square_at_distance(&mut rng, button, 5, 10);
+		let terrain = match rng.range(0, 9) {
+			0..=3 => Terrain::Mud,
+			4..=5 => Terrain::Rubble,
+			6..=7 => Terrain::Water,
+			_ => Terrain::Pit,
+		};
+		let mut candidate_terrain = layout.terrain.clone();
+		for square in random_patch(&mut rng, center) {
+			if !taken_squares.contains(&square) {
+				candidate_terrain.set(square, terrain);
+			}
+		}
+		let previous_terrain = std::mem::replace(&mut layout.terrain, candidate_terrain);
+		if !everything_reaches_button(&layout, &layout.walls) {
+			layout.terrain = previous_terrain;
+		}
+	}
+
+	//add ruins one at a time, throwing out any that would cut something off from the button
+	for _ in 0..rng.range(4, 8) {
+		let start = random_square_at_distance(&mut rng, button, 6, 10);
+		let ruin = random_ruin(&mut rng, start);
+		let mut candidate_walls = layout.walls.clone();
+		for wall in ruin {
+			let already_built = candidate_walls.iter().any(|existing| {
+				existing.endpoints == wall.endpoints || existing.endpoints == (wall.endpoints.1, wall.endpoints.0)
+			});
+			if !already_built {
+				candidate_walls.push(wall);
+			}
+		}
+		if everything_reaches_button(&layout, &candidate_walls) {
+			layout.walls = candidate_walls;
+		}
+	}
+	layout
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::battle_objects::buildables::DoorStore;
+	use crate::utils::pathing::path_to;
+
+	#[test]
+	fn generated_arenas_are_fair(){
+		for seed in 0..40 {
+			let layout = generate_arena(seed);
+			assert!(everything_reaches_button(&layout, &layout.walls), "seed {} cut something off", seed);
+		}
+	}
+
+	#[test]
+	fn long_detours_still_count_as_reaching_the_button(){
+		//a hand-drawn wall the spawn has to walk a long way around, like the ones that made the editor complain
+		let mut layout = ArenaLayout::classic();
+		layout.spawn_points = vec![SpawnPoint{ pos: GridCoord{x: 10, y: 0}, kind: EnemyKind::Melee }];
+		let walls: Vec<Wall> = (-60..60)
+				.map(|y| Wall{ endpoints: GridCoord{x: 5, y}.edge(Direction::West), health: (100, 100), material: WallMaterial::Stone, spent: 0 })
+				.collect();
+		assert!(everything_reaches_button(&layout, &walls));
+		assert!(path_to(GridCoord{x: 10, y: 0}, layout.button, &WallStore::from_items(walls.iter().copied()), &DoorStore::new(), &Vec::new(), &layout.terrain).is_none());
+	}
+
+	#[test]
+	fn generated_arenas_keep_their_ruins(){
+		assert!((0..40).all(|seed| !generate_arena(seed).walls.is_empty()));
+	}
+}
@@ -120,3 +120,23 @@ pub fn line_to_square_intersect(line: LineSegment, square: Square) -> bool {
 	false
 }
 
+
+pub fn square_to_square_overlap(first: Square, second: Square) -> Option<(i32, i32)> {
+	//returns the smallest offset that pushes the first square out of the second, if they overlap
+	let ((first_x, first_y), first_side) = first;
+	let ((second_x, second_y), second_side) = second;
+	let half_sides = (first_side + second_side) as i32 / 2;
+	let (dx, dy) = (first_x - second_x, first_y - second_y);
+	let overlap_x = half_sides - dx.abs();
+	let overlap_y = half_sides - dy.abs();
+	if overlap_x <= 0 || overlap_y <= 0 {
+		return None;
+	}
+	//squares sharing a center get pushed along x, there's no better direction to pick
+	let sign = |delta: i32| -> i32 { if delta < 0 { -1 } else { 1 } };
+	if overlap_x <= overlap_y {
+		Some((sign(dx) * overlap_x, 0))
+	} else {
+		Some((0, sign(dy) * overlap_y))
+	}
+}
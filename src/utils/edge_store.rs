@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+
+pub type Edge = (GameCoord, GameCoord);
+
+pub fn normalize_edge(edge: Edge) -> Edge{
+	//the same edge can be written with its endpoints either way round, always key it smallest first
+	if (edge.0.x, edge.0.y) <= (edge.1.x, edge.1.y) { edge } else { (edge.1, edge.0) }
+}
+
+pub fn shared_edge(first: GridCoord, second: GridCoord) -> Option<Edge>{
+	//the edge between two orthogonally adjacent squares, None if they don't touch along a side
+	match (second.x - first.x, second.y - first.y) {
+		(0, -1) => Some(first.edge(Direction::North)),
+		(0, 1) => Some(first.edge(Direction::South)),
+		(-1, 0) => Some(first.edge(Direction::West)),
+		(1, 0) => Some(first.edge(Direction::East)),
+		_ => None,
+	}
+}
+
+pub trait OnEdge{
+	fn edge(&self) -> Edge;
+}
+
+#[derive(Clone)]
+pub struct EdgeStore<T>{
+	//anything that lives on a grid edge, indexed by normalized edge so lookups don't scan every item
+	items: HashMap<Edge, T>,
+	revision: u64, //bumped every time something is added or removed, so anything drawn from the store knows when to redraw
+}
+
+impl<T: OnEdge> EdgeStore<T>{
+	pub fn new() -> EdgeStore<T>{
+		EdgeStore{ items: HashMap::new(), revision: 0 }
+	}
+
+	pub fn from_items(items: impl IntoIterator<Item = T>) -> EdgeStore<T>{
+		let mut store = EdgeStore::new();
+		for item in items {
+			store.insert(item);
+		}
+		store
+	}
+
+	pub fn insert(&mut self, item: T) -> Option<T>{
+		//returns whatever was already on the edge
+		self.revision += 1;
+		self.items.insert(normalize_edge(item.edge()), item)
+	}
+
+	pub fn remove(&mut self, edge: Edge) -> Option<T>{
+		let removed = self.items.remove(&normalize_edge(edge));
+		if removed.is_some() {
+			self.revision += 1;
+		}
+		removed
+	}
+
+	pub fn revision(&self) -> u64{
+		self.revision
+	}
+
+	pub fn get(&self, edge: Edge) -> Option<&T>{
+		self.items.get(&normalize_edge(edge))
+	}
+
+	pub fn get_mut(&mut self, edge: Edge) -> Option<&mut T>{
+		self.items.get_mut(&normalize_edge(edge))
+	}
+
+	pub fn contains(&self, edge: Edge) -> bool{
+		self.items.contains_key(&normalize_edge(edge))
+	}
+
+	pub fn on_side(&self, square: GridCoord, side: Direction) -> Option<&T>{
+		self.get(square.edge(side))
+	}
+
+	pub fn between(&self, first: GridCoord, second: GridCoord) -> Option<&T>{
+		shared_edge(first, second).and_then(|edge| self.get(edge))
+	}
+
+	pub fn is_blocking(&self, first: GridCoord, second: GridCoord) -> bool{
+		self.between(first, second).is_some()
+	}
+
+	pub fn around_squares(&self, squares: impl IntoIterator<Item = GridCoord>) -> Vec<&T>{
+		//everything on any side of the given squares, each item only once
+		let mut seen = HashSet::new();
+		let mut found = Vec::new();
+		for square in squares {
+			for side in [Direction::North, Direction::South, Direction::West, Direction::East] {
+				let edge = normalize_edge(square.edge(side));
+				if seen.insert(edge) {
+					if let Some(item) = self.items.get(&edge) {
+						found.push(item);
+					}
+				}
+			}
+		}
+		found
+	}
+
+	pub fn near(&self, center: GridCoord, reach: i32) -> Vec<&T>{
+		//everything bordering the square block of squares within reach of center
+		let squares = (-reach..=reach).flat_map(|x| (-reach..=reach).map(move |y| center.offset((x, y))));
+		self.around_squares(squares)
+	}
+
+	pub fn along_segment(&self, from: GameCoord, to: GameCoord) -> Vec<&T>{
+		//everything bordering the squares a straight line passes through, sampled finely enough not to skip a square
+		let length = from.pythagorean_distance_to(&to);
+		let step = GridCoord::grid_size() as f32 / 10.0;
+		let samples = (length / step).ceil().max(1.0) as i32;
+		let mut squares = HashSet::new();
+		for sample in 0..=samples {
+			let progress = sample as f32 / samples as f32;
+			let point = GameCoord{
+				x: from.x + ((to.x - from.x) as f32 * progress) as i32,
+				y: from.y + ((to.y - from.y) as f32 * progress) as i32,
+			};
+			squares.insert(point.to_grid_coord());
+		}
+		self.around_squares(squares)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &T>{
+		self.items.values()
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T>{
+		self.items.values_mut()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	struct Marker(Edge);
+
+	impl OnEdge for Marker{
+		fn edge(&self) -> Edge{
+			self.0
+		}
+	}
+
+	fn at(x: i32, y: i32) -> GridCoord{
+		GridCoord{x, y}
+	}
+
+	fn reversed(edge: Edge) -> Edge{
+		(edge.1, edge.0)
+	}
+
+	#[test]
+	fn endpoint_order_doesnt_matter(){
+		let edge = at(2, 3).edge(Direction::North);
+		assert_eq!(normalize_edge(edge), normalize_edge(reversed(edge)));
+		let mut store = EdgeStore::new();
+		store.insert(Marker(reversed(edge)));
+		assert!(store.contains(edge));
+		assert_eq!(store.get(edge), Some(&Marker(reversed(edge))));
+		assert!(store.insert(Marker(edge)).is_some());
+		assert_eq!(store.iter().count(), 1);
+		assert!(store.remove(reversed(edge)).is_some());
+		assert!(!store.contains(edge));
+	}
+
+	#[test]
+	fn on_side_finds_the_edge_from_either_square(){
+		let store = EdgeStore::from_items([Marker(at(0, 0).edge(Direction::East))]);
+		assert!(store.on_side(at(0, 0), Direction::East).is_some());
+		assert!(store.on_side(at(1, 0), Direction::West).is_some());
+		assert!(store.on_side(at(0, 0), Direction::West).is_none());
+		assert!(store.on_side(at(1, 0), Direction::East).is_none());
+	}
+
+	#[test]
+	fn between_only_looks_at_shared_sides(){
+		let store = EdgeStore::from_items([Marker(at(0, 0).edge(Direction::South))]);
+		assert!(store.between(at(0, 0), at(0, 1)).is_some());
+		assert!(store.between(at(0, 1), at(0, 0)).is_some());
+		assert!(store.between(at(0, 0), at(1, 0)).is_none());
+		//diagonal and distant squares never share an edge
+		assert!(store.between(at(0, 0), at(1, 1)).is_none());
+		assert!(store.between(at(0, 0), at(0, 2)).is_none());
+	}
+
+	#[test]
+	fn along_segment_finds_edges_the_line_passes(){
+		let crossed = at(3, 0).edge(Direction::East);
+		let far_away = at(3, 6).edge(Direction::East);
+		let store = EdgeStore::from_items([Marker(crossed), Marker(far_away)]);
+		let found = store.along_segment(at(0, 0).center(), at(6, 0).center());
+		assert_eq!(found, vec![&Marker(crossed)]);
+		assert!(store.along_segment(at(0, 3).center(), at(6, 3).center()).is_empty());
+	}
+
+	#[test]
+	fn revision_only_changes_when_the_store_does(){
+		let mut store = EdgeStore::new();
+		let edge = at(0, 0).edge(Direction::North);
+		store.insert(Marker(edge));
+		let revision = store.revision();
+		assert!(store.remove(at(5, 5).edge(Direction::North)).is_none());
+		assert_eq!(store.revision(), revision);
+		store.remove(edge);
+		assert!(store.revision() > revision);
+	}
+}
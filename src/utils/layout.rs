@@ -0,0 +1,57 @@
+use sdl2::rect::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor{
+	TopLeft,
+	TopRight,
+	Center,
+	BottomLeft,
+	Bottom,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Layout{
+	//places HUD and menu elements relative to the edges of the screen
+	//offsets and sizes are given in reference pixels and grow or shrink with the window
+	screen: (u32, u32),
+	scale: f32,
+}
+
+impl Layout{
+	pub fn reference_size() -> (u32, u32){
+		//the window size all the menus were first laid out at
+		(1080, 720)
+	}
+
+	pub fn new(screen: (u32, u32)) -> Layout{
+		let reference = Layout::reference_size();
+		let scale = (screen.0 as f32 / reference.0 as f32).min(screen.1 as f32 / reference.1 as f32);
+		Layout{ screen, scale }
+	}
+
+	pub fn scaled(&self, length: u32) -> u32{
+		((length as f32 * self.scale).round() as u32).max(1)
+	}
+
+	pub fn rect(&self, anchor: Anchor, offset: (i32, i32), size: (u32, u32)) -> Rect{
+		//offset pushes the rect in from the edges it's anchored to, and along from the middle on centered axes
+		let (width, height) = (self.scaled(size.0), self.scaled(size.1));
+		let offset_x = (offset.0 as f32 * self.scale).round() as i32;
+		let offset_y = (offset.1 as f32 * self.scale).round() as i32;
+		let x = match anchor {
+			Anchor::TopLeft | Anchor::BottomLeft => offset_x,
+			Anchor::Center | Anchor::Bottom => (self.screen.0 as i32 - width as i32) / 2 + offset_x,
+			Anchor::TopRight => self.screen.0 as i32 - width as i32 - offset_x,
+		};
+		let y = match anchor {
+			Anchor::TopLeft | Anchor::TopRight => offset_y,
+			Anchor::Center => (self.screen.1 as i32 - height as i32) / 2 + offset_y,
+			Anchor::BottomLeft | Anchor::Bottom => self.screen.1 as i32 - height as i32 - offset_y,
+		};
+		Rect::new(x, y, width, height)
+	}
+
+	pub fn fill(&self) -> Rect{
+		Rect::new(0, 0, self.screen.0, self.screen.1)
+	}
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::Path;
+use crate::battle_objects::ability_plots::AbilityPlot;
+use crate::battle_objects::arena::{ArenaLayout, SpawnPoint};
+use crate::battle_objects::battle_player::Ability;
+use crate::battle_objects::buildables::{Wall, WallMaterial, Window};
+use crate::battle_objects::coordinates::{GameCoord, GridCoord};
+use crate::battle_objects::enemy::EnemyKind;
+use crate::battle_objects::terrain::{Terrain, TerrainMap};
+
+//maps are plain text, one object per line:
+//  button <x> <y>
+//  player_start <x> <y>                  (game coordinates)
+//  plot <x> <y> <ability>
+//  wall <x1> <y1> <x2> <y2> <health> <max health> [material]      (corner game coordinates, stone if no material)
+//  window <x1> <y1> <x2> <y2> <health> <max health>
+//  spawn <x> <y> <enemy kind>
+//  terrain <x> <y> <terrain>             (squares without a terrain line are floor)
+//blank lines and lines starting with # are ignored
+
+pub fn layout_to_string(layout: &ArenaLayout) -> String{
+	let mut lines = vec![String::from("# Button Game map")];
+	lines.push(format!("button {} {}", layout.button.x, layout.button.y));
+	lines.push(format!("player_start {} {}", layout.player_start.x, layout.player_start.y));
+	for plot in &layout.ability_plots {
+		lines.push(format!("plot {} {} {:?}", plot.pos.x, plot.pos.y, plot.ability));
+	}
+	for wall in &layout.walls {
+		lines.push(format!("wall {} {} {} {} {} {} {:?}", wall.endpoints.0.x, wall.endpoints.0.y, wall.endpoints.1.x, wall.endpoints.1.y, wall.health.0, wall.health.1, wall.material));
+	}
+	for window in &layout.windows {
+		lines.push(format!("window {} {} {} {} {} {}", window.endpoints.0.x, window.endpoints.0.y, window.endpoints.1.x, window.endpoints.1.y, window.health.0, window.health.1));
+	}
+	for spawn in &layout.spawn_points {
+		lines.push(format!("spawn {} {} {:?}", spawn.pos.x, spawn.pos.y, spawn.kind));
+	}
+	let mut tiles = layout.terrain.tiles().collect::<Vec<_>>();
+	//the map is a hash map, sort so saving the same layout twice gives the same file
+	tiles.sort_by_key(|(square, _)| (square.y, square.x));
+	for (square, terrain) in tiles {
+		lines.push(format!("terrain {} {} {:?}", square.x, square.y, terrain));
+	}
+	lines.join("\n") + "\n"
+}
+
+pub fn layout_from_string(text: &str) -> Result<ArenaLayout, String>{
+	let mut layout = ArenaLayout{
+		button: GridCoord{x: 0, y: 0},
+		player_start: GameCoord{x: 0, y: 0},
+		ability_plots: Vec::new(),
+		walls: Vec::new(),
+		windows: Vec::new(),
+		spawn_points: Vec::new(),
+		terrain: TerrainMap::new(),
+	};
+	for (line_index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let fields: Vec<&str> = line.split_whitespace().collect();
+		let error = |reason: &str| format!("Line {}: {} ({})", line_index + 1, reason, line);
+		let number = |index: usize| -> Result<i32, String> {
+			fields.get(index)
+				.ok_or_else(|| error("missing field"))?
+				.parse::<i32>()
+				.map_err(|_| error("expected a number"))
+		};
+		let health = |index: usize| -> Result<(usize, usize), String> {
+			//hand-edited maps can claim more health than the max, which nothing downstream expects
+			let max = number(index + 1)?.max(1) as usize;
+			Ok((number(index)?.clamp(1, max as i32) as usize, max))
+		};
+		let text_field = |index: usize| -> Result<&str, String> {
+			fields.get(index).copied().ok_or_else(|| error("missing field"))
+		};
+		match fields[0] {
+			"button" => layout.button = GridCoord{x: number(1)?, y: number(2)?},
+			"player_start" => layout.player_start = GameCoord{x: number(1)?, y: number(2)?},
+			"plot" => {
+				let ability = Ability::from_name(text_field(3)?).ok_or_else(|| error("unknown ability"))?;
+				layout.ability_plots.push(AbilityPlot{pos: GridCoord{x: number(1)?, y: number(2)?}, ability});
+			},
+			"wall" => layout.walls.push(Wall{
+				endpoints: (GameCoord{x: number(1)?, y: number(2)?}, GameCoord{x: number(3)?, y: number(4)?}),
+				health: health(5)?,
+				material: match fields.get(7) {
+					Some(name) => WallMaterial::from_name(name).ok_or_else(|| error("unknown wall material"))?,
+					None => WallMaterial::Stone,
+				},
+				spent: 0,
+			}),
+			"window" => layout.windows.push(Window{
+				endpoints: (GameCoord{x: number(1)?, y: number(2)?}, GameCoord{x: number(3)?, y: number(4)?}),
+				health: health(5)?,
+			}),
+			"spawn" => {
+				let kind = match text_field(3)? {
+					"Melee" => EnemyKind::Melee,
+					"Ranged" => EnemyKind::Ranged,
+					_ => return Err(error("unknown enemy kind")),
+				};
+				layout.spawn_points.push(SpawnPoint{pos: GridCoord{x: number(1)?, y: number(2)?}, kind});
+			},
+			"terrain" => {
+				let terrain = Terrain::from_name(text_field(3)?).ok_or_else(|| error("unknown terrain"))?;
+				layout.terrain.set(GridCoord{x: number(1)?, y: number(2)?}, terrain);
+			},
+			_ => return Err(error("unknown entry")),
+		}
+	}
+	Ok(layout)
+}
+
+pub fn save_layout(layout: &ArenaLayout, path: &str) -> Result<(), String>{
+	if let Some(parent) = Path::new(path).parent() {
+		fs::create_dir_all(parent).map_err(|why| why.to_string())?;
+	}
+	fs::write(path, layout_to_string(layout)).map_err(|why| why.to_string())
+}
+
+pub fn load_layout(path: &str) -> Result<ArenaLayout, String>{
+	let text = fs::read_to_string(path).map_err(|why| why.to_string())?;
+	layout_from_string(&text)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::arena_generation::generate_arena;
+
+	#[test]
+	fn classic_layout_round_trips(){
+		let text = layout_to_string(&ArenaLayout::classic());
+		let loaded = layout_from_string(&text).unwrap();
+		assert_eq!(layout_to_string(&loaded), text);
+	}
+
+	#[test]
+	fn generated_layouts_round_trip(){
+		for seed in 0..20 {
+			let text = layout_to_string(&generate_arena(seed));
+			let loaded = layout_from_string(&text).unwrap();
+			assert_eq!(layout_to_string(&loaded), text, "seed {}", seed);
+		}
+	}
+
+	#[test]
+	fn health_above_max_is_clamped(){
+		let layout = layout_from_string("wall 0 0 20 0 900 100 Wood\nwindow 0 0 0 20 50 10\n").unwrap();
+		assert_eq!(layout.walls[0].health, (100, 100));
+		assert_eq!(layout.windows[0].health, (10, 10));
+		let reloaded = layout_from_string(&layout_to_string(&layout)).unwrap();
+		assert_eq!(reloaded.walls[0].health, (100, 100));
+		assert_eq!(reloaded.windows[0].health, (10, 10));
+	}
+
+	#[test]
+	fn health_is_at_least_one(){
+		let layout = layout_from_string("wall 0 0 20 0 0 0\n").unwrap();
+		assert_eq!(layout.walls[0].health, (1, 1));
+	}
+}
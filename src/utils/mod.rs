@@ -1,4 +1,10 @@
 pub(crate) mod render_utils;
 pub (crate) mod time_utils;
 pub(crate) mod collisions;
-pub(crate) mod pathing;
\ No newline at end of file
+pub(crate) mod pathing;
+pub(crate) mod edge_store;
+pub(crate) mod visibility;
+pub(crate) mod random;
+pub(crate) mod arena_generation;
+pub(crate) mod map_format;
+pub(crate) mod layout;
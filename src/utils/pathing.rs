@@ -1,7 +1,10 @@
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
-use crate::battle_objects::buildables::Wall;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::battle_objects::buildables::{DoorStore, WallStore};
+use crate::battle_objects::faction::Faction;
 use crate::battle_objects::coordinates::GridCoord;
+use crate::battle_objects::terrain::TerrainMap;
+use crate::utils::edge_store::{normalize_edge, shared_edge, Edge};
 
 #[derive(Clone, Copy)]
 struct PathingNode{
@@ -33,9 +36,25 @@ impl Ord for PathingNode{
 pub fn path_to(
 	from: GridCoord,
 	to: GridCoord,
-	walls: &Vec<Wall>,
+	walls: &WallStore,
+	doors: &DoorStore,
 	blocked_squares: &Vec<GridCoord>,
+	terrain: &TerrainMap,
 ) -> Option<Vec<GridCoord>> {
+	path_to_with_footprint(from, to, walls, doors, blocked_squares, terrain, 1)
+}
+
+pub fn path_to_with_footprint(
+	from: GridCoord,
+	to: GridCoord,
+	walls: &WallStore,
+	doors: &DoorStore,
+	blocked_squares: &Vec<GridCoord>,
+	terrain: &TerrainMap,
+	footprint: i32,
+) -> Option<Vec<GridCoord>> {
+	//footprint is the side length in grid squares of a square body centered on the path, it should be odd
+	//only hostiles path, so doors are as solid as walls here
 	let mut loop_iterations = 0;
 	if from == to {
 		return Some(Vec::new());
@@ -50,11 +69,33 @@ pub fn path_to(
 	g_score.insert(from, 0.0);
 	f_score.insert(from, from.pythagorean_distance_to(&to));
 
+	let reach = footprint / 2;
+	let footprint_offsets: Vec<(i32, i32)> = (-reach..=reach)
+			.flat_map(|x| (-reach..=reach).map(move |y| (x, y)))
+			.collect();
+	let is_blocking = |first: GridCoord, second: GridCoord| -> bool {
+		walls.is_blocking(first, second) || doors.is_blocking_for(first, second, Faction::Hostile)
+	};
+	let can_step = |square: GridCoord, next: GridCoord| -> bool {
+		footprint_offsets.iter().all(|&offset| {
+			let body_square = square.offset(offset);
+			let next_body_square = next.offset(offset);
+			if blocked_squares.contains(&next_body_square) || !terrain.get(next_body_square).is_passable() || is_blocking(body_square, next_body_square) {
+				return false;
+			}
+			//a wide body can't end up straddling a wall either
+			let east_neighbor = next_body_square.to_east(1);
+			let south_neighbor = next_body_square.to_south(1);
+			(offset.0 == reach || !is_blocking(next_body_square, east_neighbor)) &&
+					(offset.1 == reach || !is_blocking(next_body_square, south_neighbor))
+		})
+	};
+
 	let connected_squares = |square: GridCoord| -> Vec<GridCoord> {
 		let mut connected = Vec::new();
 		let directions = [square.to_north(1), square.to_south(1), square.to_west(1), square.to_east(1)];
 		for &dir in directions.iter() {
-			if !blocked_squares.contains(&dir) && !walls.iter().any(|wall| wall.is_blocking(square, dir)) {
+			if can_step(square, dir) {
 				connected.push(dir);
 			}
 		}
@@ -80,7 +121,7 @@ pub fn path_to(
 		}
 
 		for neighbor in connected_squares(current.position) {
-			let tentative_g_score = g_score[&current.position] + 1.0;
+			let tentative_g_score = g_score[&current.position] + terrain.get(neighbor).step_cost();
 			if tentative_g_score < *g_score.get(&neighbor).unwrap_or(&f32::MAX) {
 				came_from.insert(neighbor, current.position);
 				g_score.insert(neighbor, tentative_g_score);
@@ -92,4 +133,90 @@ pub fn path_to(
 	}
 	println!("Exhausted open set without finding a path");
 	None
-}
\ No newline at end of file
+}
+
+pub fn is_reachable(from: GridCoord, to: GridCoord, can_step: impl Fn(GridCoord, GridCoord) -> bool) -> bool{
+	//floods out from both ends a square at a time. Walls and terrain only cover a finite patch of the map,
+	//so at most one end can be in open country and the other runs out of squares, no iteration cap needed
+	if from == to {
+		return true;
+	}
+	let mut seen = [HashSet::from([from]), HashSet::from([to])];
+	let mut frontiers = [VecDeque::from([from]), VecDeque::from([to])];
+	loop {
+		for side in 0..2 {
+			let Some(square) = frontiers[side].pop_front() else { return false };
+			for next in [square.to_north(1), square.to_south(1), square.to_west(1), square.to_east(1)] {
+				//the flood from the far end runs backwards, so check the step the way a body would take it
+				let allowed = if side == 0 { can_step(square, next) } else { can_step(next, square) };
+				if !allowed || seen[side].contains(&next) {
+					continue;
+				}
+				if seen[1 - side].contains(&next) {
+					return true;
+				}
+				seen[side].insert(next);
+				frontiers[side].push_back(next);
+			}
+		}
+	}
+}
+
+pub fn path_crosses_wall(from: GridCoord, path: &Vec<GridCoord>, wall: Edge) -> bool {
+	//walk every step of the remaining path, starting from the square we're currently standing in
+	let wall = normalize_edge(wall);
+	let mut previous = from;
+	for &step in path.iter() {
+		if shared_edge(previous, step).map_or(false, |edge| normalize_edge(edge) == wall) {
+			return true;
+		}
+		previous = step;
+	}
+	false
+}
+
+pub fn path_passes_near_wall(from: GridCoord, path: &Vec<GridCoord>, wall: Edge) -> bool {
+	//a removed wall can only shorten paths that run close to it, so only check the path's bounding box
+	let (mut min_x, mut max_x, mut min_y, mut max_y) = (from.x, from.x, from.y, from.y);
+	for step in path.iter() {
+		min_x = min_x.min(step.x);
+		max_x = max_x.max(step.x);
+		min_y = min_y.min(step.y);
+		max_y = max_y.max(step.y);
+	}
+	let wall_squares = [wall.0.to_grid_coord(), wall.1.to_grid_coord()];
+	wall_squares.iter().any(|square| {
+		square.x >= min_x - 1 && square.x <= max_x + 1 && square.y >= min_y - 1 && square.y <= max_y + 1
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::battle_objects::coordinates::Direction;
+
+	fn at(x: i32, y: i32) -> GridCoord{
+		GridCoord{x, y}
+	}
+
+	fn boxed_in(square: GridCoord) -> impl Fn(GridCoord, GridCoord) -> bool{
+		//a single walled-in square in otherwise open ground
+		let sides = [Direction::North, Direction::South, Direction::West, Direction::East].map(|side| normalize_edge(square.edge(side)));
+		move |from, to| shared_edge(from, to).is_none_or(|edge| !sides.contains(&normalize_edge(edge)))
+	}
+
+	#[test]
+	fn open_ground_is_reachable(){
+		assert!(is_reachable(at(0, 0), at(40, -25), |_, _| true));
+	}
+
+	#[test]
+	fn boxed_in_goal_is_unreachable(){
+		assert!(!is_reachable(at(0, 0), at(30, 30), boxed_in(at(30, 30))));
+	}
+
+	#[test]
+	fn boxed_in_start_is_unreachable(){
+		assert!(!is_reachable(at(30, 30), at(0, 0), boxed_in(at(30, 30))));
+	}
+}
@@ -0,0 +1,40 @@
+#[derive(Clone, Copy, Debug)]
+pub struct SeededRng{
+	state: u64,
+}
+
+impl SeededRng{
+	pub fn new(seed: u64) -> SeededRng{
+		//xorshift gets stuck on a zero state, so mix the seed first
+		let mut state = seed ^ 0x9E37_79B9_7F4A_7C15;
+		if state == 0 {
+			state = 0x2545_F491_4F6C_DD1D;
+		}
+		SeededRng{ state }
+	}
+
+	pub fn next_u64(&mut self) -> u64{
+		//xorshift64*
+		self.state ^= self.state >> 12;
+		self.state ^= self.state << 25;
+		self.state ^= self.state >> 27;
+		self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+	}
+
+	pub fn next_f32(&mut self) -> f32{
+		(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+	}
+
+	pub fn range(&mut self, min: i32, max: i32) -> i32{
+		//inclusive on both ends
+		if max <= min {
+			return min;
+		}
+		let span = (max - min + 1) as u64;
+		min + (self.next_u64() % span) as i32
+	}
+
+	pub fn chance(&mut self, probability: f32) -> bool{
+		self.next_f32() < probability
+	}
+}
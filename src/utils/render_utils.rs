@@ -1,8 +1,8 @@
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::ttf::Sdl2TtfContext;
+use crate::asset_cache::AssetCache;
 
-pub fn render_progress_bar(canvas: &mut sdl2::render::WindowCanvas, x: i32, y: i32, width: u32, height: u32, progress: (usize, usize)){
+pub fn render_progress_bar<T: sdl2::render::RenderTarget>(canvas: &mut sdl2::render::Canvas<T>, x: i32, y: i32, width: u32, height: u32, progress: (usize, usize)){
 	let progress_pct = progress.0 as f32 / progress.1 as f32;
 	let progress_pixels = (height as f32 * progress_pct) as u32;
 	let progress_rectangle = Rect::new(
@@ -15,12 +15,7 @@ pub fn render_progress_bar(canvas: &mut sdl2::render::WindowCanvas, x: i32, y: i
 	canvas.fill_rect(progress_rectangle).unwrap();
 }
 
-pub fn render_text(canvas: &mut sdl2::render::WindowCanvas, ttf_context: &Sdl2TtfContext, text: &str, font_size: u16, color: Color, target: Rect){
-	let font = ttf_context.load_font("assets/fonts/The_Frontman.ttf", font_size).unwrap();
-	let surface = font.render(text)
-		.blended(color)
-		.unwrap();
-	let texture_creator = canvas.texture_creator();
-	let texture = texture_creator.create_texture_from_surface(&surface).unwrap();
-	canvas.copy(&texture, None, Some(target)).expect("Couldn't write start screen text.");
+pub fn render_text<T: sdl2::render::RenderTarget>(canvas: &mut sdl2::render::Canvas<T>, assets: &mut AssetCache<T::Context>, text: &str, font_size: u16, color: Color, target: Rect){
+	let texture = assets.text(text, font_size, color);
+	canvas.copy(texture, None, Some(target)).expect("Couldn't write start screen text.");
 }
\ No newline at end of file
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::f32::consts::PI;
+use crate::battle_objects::buildables::WallStore;
+use crate::battle_objects::coordinates::{Direction, GameCoord, GridCoord};
+use crate::utils::collisions::line_to_line_intersect;
+use crate::utils::edge_store::{normalize_edge, Edge};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ViewCone{
+	pub facing: f32, //radians, the same way round as facing vectors
+	pub half_angle: f32, //how far either side of facing we can see
+}
+
+pub fn visible_squares_from(origin: GridCoord, facing: Direction, vision_range: u8, walls: &WallStore) -> HashSet<GridCoord>{
+	//a quarter turn of view centered on where we're looking
+	field_of_view(origin, vision_range, Some(ViewCone{ facing: facing.to_facing_vector(), half_angle: 0.25*PI }), walls)
+}
+
+pub fn field_of_view(origin: GridCoord, radius: u8, cone: Option<ViewCone>, walls: &WallStore) -> HashSet<GridCoord>{
+	//shadowcasting over grid edges, one quadrant at a time
+	//a square is visible if the line from our center to its center doesn't cross a wall, so a sees b exactly when b sees a
+	let mut visible = HashSet::new();
+	visible.insert(origin);
+	for quadrant in [Direction::North, Direction::South, Direction::East, Direction::West] {
+		cast_quadrant(origin, quadrant, radius as i32, walls, &mut visible);
+	}
+	if let Some(cone) = cone {
+		//the squares right next to us are always noticed, everything further away has to be in front of us
+		visible.retain(|square| {
+			let (dx, dy) = (square.x - origin.x, square.y - origin.y);
+			if dx.abs() <= 1 && dy.abs() <= 1 {
+				return true;
+			}
+			let angle = (-dy as f32).atan2(dx as f32);
+			let mut difference = (angle - cone.facing) % (2.0*PI);
+			if difference > PI {
+				difference -= 2.0*PI;
+			} else if difference < -PI {
+				difference += 2.0*PI;
+			}
+			difference.abs() <= cone.half_angle + 0.0001
+		});
+	}
+	visible
+}
+
+fn quadrant_square(origin: GridCoord, quadrant: Direction, row: i32, col: i32) -> GridCoord{
+	//row counts away from the origin, col counts across
+	match quadrant {
+		Direction::North => origin.offset((col, -row)),
+		Direction::South => origin.offset((col, row)),
+		Direction::East => origin.offset((row, col)),
+		Direction::West => origin.offset((-row, col)),
+	}
+}
+
+fn cast_quadrant(origin: GridCoord, quadrant: Direction, radius: i32, walls: &WallStore, visible: &mut HashSet<GridCoord>){
+	//slopes are col/row, the quadrant covers -1 to 1
+	//open intervals are the rays that haven't hit a wall yet, a ray that so much as touches the end of a wall is stopped by it
+	//so nothing can be seen through the joint where two walls meet
+	let mut open_intervals: Vec<(f32, f32)> = vec![(-1.0, 1.0)];
+	let square = |row: i32, col: i32| quadrant_square(origin, quadrant, row, col);
+	for row in 1..=radius {
+		if open_intervals.is_empty() {
+			break;
+		}
+		let near = row as f32 - 0.5;
+		let middle = row as f32;
+		let far = row as f32 + 0.5;
+		let mut blocked: Vec<(f32, f32)> = Vec::new();
+		let mut blocked_beyond: Vec<(f32, f32)> = Vec::new();
+		//walls across the quadrant between the previous row and this one
+		for col in -row..=row {
+			if walls.is_blocking(square(row - 1, col), square(row, col)) {
+				blocked.push(((col as f32 - 0.5) / near, (col as f32 + 0.5) / near));
+			}
+		}
+		//walls running away from us between two squares of this row
+		//only the near half can hide a square in this row, the far half only matters from the next row on
+		for col in -(row + 1)..=row {
+			if walls.is_blocking(square(row, col), square(row, col + 1)) {
+				blocked.push(edge_slopes(col as f32 + 0.5, near, middle));
+				blocked_beyond.push(edge_slopes(col as f32 + 0.5, middle, far));
+			}
+		}
+		if row == 1 {
+			//walls on either side of our own square only cover the half of it in this quadrant
+			for col in -1..=0 {
+				if walls.is_blocking(square(0, col), square(0, col + 1)) {
+					blocked.push(edge_slopes(col as f32 + 0.5, 0.0, 0.5));
+				}
+			}
+		}
+		open_intervals = cast_shadows(open_intervals, blocked);
+		for col in -row..=row {
+			if row*row + col*col > radius*radius {
+				continue;
+			}
+			let slope = col as f32 / row as f32;
+			if open_intervals.iter().any(|(start, end)| *start <= slope && slope <= *end) {
+				visible.insert(square(row, col));
+			}
+		}
+		open_intervals = cast_shadows(open_intervals, blocked_beyond);
+	}
+}
+
+fn cast_shadows(open_intervals: Vec<(f32, f32)>, shadows: Vec<(f32, f32)>) -> Vec<(f32, f32)>{
+	shadows.into_iter().fold(open_intervals, |remaining, shadow| {
+		remaining.into_iter().flat_map(|open| subtract_interval(open, shadow)).collect()
+	})
+}
+
+fn edge_slopes(col: f32, near: f32, far: f32) -> (f32, f32){
+	//the slopes covered by an edge running away from us at a fixed col, from near to far
+	let near_slope = if near == 0.0 { col.signum() * f32::INFINITY } else { col / near };
+	let far_slope = col / far;
+	(near_slope.min(far_slope), near_slope.max(far_slope))
+}
+
+fn subtract_interval(open: (f32, f32), shadow: (f32, f32)) -> Vec<(f32, f32)>{
+	//shadows include their own ends, so they're widened by a hair less than the gap between any two slopes in range
+	let shadow = (shadow.0 - 0.0001, shadow.1 + 0.0001);
+	let mut remaining = Vec::new();
+	if open.0 < shadow.0 {
+		remaining.push((open.0, open.1.min(shadow.0)));
+	}
+	if shadow.1 < open.1 {
+		remaining.push((open.0.max(shadow.1), open.1));
+	}
+	remaining
+}
+
+pub fn has_line_of_sight(from: GameCoord, to: GameCoord, walls: &WallStore, ignored: Option<Edge>) -> bool{
+	//ignored is usually the wall being aimed at, which shouldn't count as blocking the shot at itself
+	let ignored = ignored.map(normalize_edge);
+	walls.along_segment(from, to).iter().filter(|wall| Some(normalize_edge(wall.endpoints)) != ignored).all(|wall| {
+		!line_to_line_intersect((from.into(), to.into()), (wall.endpoints.0.into(), wall.endpoints.1.into()))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::battle_objects::buildables::{Wall, WallMaterial};
+
+	fn walls_on(edges: &[(GridCoord, Direction)]) -> WallStore{
+		WallStore::from_items(edges.iter().map(|(square, side)| Wall::new(square.edge(*side), WallMaterial::Stone)))
+	}
+
+	fn at(x: i32, y: i32) -> GridCoord{
+		GridCoord{x, y}
+	}
+
+	#[test]
+	fn open_ground_sees_everything_in_range(){
+		let visible = field_of_view(at(0, 0), 4, None, &WallStore::new());
+		for x in -5..=5 {
+			for y in -5..=5 {
+				assert_eq!(visible.contains(&at(x, y)), x*x + y*y <= 16, "square {}, {}", x, y);
+			}
+		}
+	}
+
+	#[test]
+	fn wall_casts_a_shadow(){
+		let walls = walls_on(&[(at(0, -1), Direction::North)]);
+		let visible = field_of_view(at(0, 0), 6, None, &walls);
+		assert!(visible.contains(&at(0, -1)));
+		assert!(!visible.contains(&at(0, -2)));
+		assert!(!visible.contains(&at(0, -5)));
+		assert!(visible.contains(&at(3, -2)));
+	}
+
+	#[test]
+	fn closed_room_hides_everything_outside(){
+		let mut edges = Vec::new();
+		for offset in -1..=1 {
+			edges.push((at(offset, -1), Direction::North));
+			edges.push((at(offset, 1), Direction::South));
+			edges.push((at(-1, offset), Direction::West));
+			edges.push((at(1, offset), Direction::East));
+		}
+		let visible = field_of_view(at(0, 0), 8, None, &walls_on(&edges));
+		let expected: HashSet<GridCoord> = (-1..=1).flat_map(|x| (-1..=1).map(move |y| at(x, y))).collect();
+		assert_eq!(visible, expected);
+	}
+
+	#[test]
+	fn cant_peek_through_the_joint_of_a_corner(){
+		//the line to the diagonal square passes exactly through the corner where these two walls meet
+		let walls = walls_on(&[(at(1, 0), Direction::North), (at(1, -1), Direction::West)]);
+		let visible = field_of_view(at(0, 0), 6, None, &walls);
+		assert!(!visible.contains(&at(1, -1)));
+		assert!(!visible.contains(&at(3, -3)));
+	}
+
+	#[test]
+	fn can_see_along_a_wall_face(){
+		let edges: Vec<(GridCoord, Direction)> = (-2..=0).map(|y| (at(0, y), Direction::East)).collect();
+		let visible = field_of_view(at(0, 0), 6, None, &walls_on(&edges));
+		assert!(visible.contains(&at(0, -5)));
+		assert!(!visible.contains(&at(1, -1)));
+		assert!(!visible.contains(&at(2, -4)));
+		assert!(visible.contains(&at(-2, -4)));
+	}
+
+	#[test]
+	fn cant_see_around_a_corner(){
+		//standing beside the end of a wall running north, squares behind it stay hidden
+		let edges: Vec<(GridCoord, Direction)> = (-4..=0).map(|y| (at(1, y), Direction::East)).collect();
+		let visible = field_of_view(at(1, 0), 8, None, &walls_on(&edges));
+		assert!(visible.contains(&at(1, -4)));
+		assert!(!visible.contains(&at(2, -1)));
+		assert!(!visible.contains(&at(3, -4)));
+		assert!(visible.contains(&at(3, 3)));
+	}
+
+	#[test]
+	fn window_in_a_wall_shows_a_wedge_beyond_it(){
+		//a wall across the north with a one square gap straight ahead
+		let edges: Vec<(GridCoord, Direction)> = (-4..=4)
+				.filter(|x| *x != 0)
+				.map(|x| (at(x, -1), Direction::North))
+				.collect();
+		let visible = field_of_view(at(0, 0), 6, None, &walls_on(&edges));
+		assert!(visible.contains(&at(0, -2)));
+		assert!(visible.contains(&at(0, -5)));
+		assert!(visible.contains(&at(1, -5)));
+		assert!(!visible.contains(&at(3, -5)));
+		assert!(!visible.contains(&at(-2, -2)));
+	}
+
+	#[test]
+	fn vision_is_symmetric(){
+		let walls = walls_on(&[
+			(at(2, 0), Direction::West),
+			(at(2, -1), Direction::West),
+			(at(-1, -2), Direction::North),
+			(at(0, -2), Direction::North),
+			(at(-3, 1), Direction::East),
+			(at(1, 3), Direction::South),
+			(at(2, 2), Direction::East),
+		]);
+		let squares: Vec<GridCoord> = (-5..=5).flat_map(|x| (-5..=5).map(move |y| at(x, y))).collect();
+		let views: Vec<HashSet<GridCoord>> = squares.iter().map(|square| field_of_view(*square, 8, None, &walls)).collect();
+		for (first_index, first) in squares.iter().enumerate() {
+			for (second_index, second) in squares.iter().enumerate() {
+				assert_eq!(views[first_index].contains(second), views[second_index].contains(first), "{:?} and {:?}", first, second);
+			}
+		}
+	}
+
+	#[test]
+	fn cone_only_covers_the_facing_side(){
+		let visible = visible_squares_from(at(0, 0), Direction::North, 5, &WallStore::new());
+		assert!(visible.contains(&at(0, -5)));
+		assert!(visible.contains(&at(2, -3)));
+		assert!(!visible.contains(&at(3, -1)));
+		assert!(!visible.contains(&at(0, 3)));
+		assert!(visible.contains(&at(0, 1)));
+	}
+}